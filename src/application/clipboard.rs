@@ -1,32 +1,89 @@
+use anyhow::anyhow;
+#[cfg(not(target_arch = "wasm32"))]
 use clipboard::ClipboardProvider;
+use eframe::egui;
 
 pub enum ClipboardHandler {
+    #[cfg(not(target_arch = "wasm32"))]
     Desktop(clipboard::ClipboardContext),
-    Web(),
+    Web(WebClipboard),
+    /// Clipboard that only lives inside the application, for headless use and tests.
+    Memory(String),
+}
+
+/// Browsers only hand out the clipboard on a paste event, and only take text on a copy,
+/// so this goes through egui's own copy and paste handling. See [`ClipboardHandler::sync_egui`].
+#[derive(Default)]
+pub struct WebClipboard {
+    pasted: String,
+    to_copy: Option<String>,
 }
 
 impl ClipboardHandler {
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn new() -> Self {
-        Self::Desktop(clipboard::ClipboardProvider::new().unwrap())
+    pub fn new() -> anyhow::Result<Self> {
+        clipboard::ClipboardProvider::new()
+            .map(Self::Desktop)
+            .map_err(|e| anyhow!("Could not access the system clipboard: {e}"))
     }
 
     #[cfg(target_arch = "wasm32")]
-    pub fn new() -> Self {
-        unimplemented!()
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self::Web(WebClipboard::default()))
+    }
+
+    pub fn memory() -> Self {
+        Self::Memory(String::default())
     }
 
-    pub fn get_clipboard(&mut self) -> String {
+    pub fn get_clipboard(&mut self) -> anyhow::Result<String> {
         match self {
-            Self::Desktop(context) => context.get_contents().unwrap(),
-            Self::Web() => unimplemented!(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Desktop(context) => context
+                .get_contents()
+                .map_err(|e| anyhow!("Could not read text from the clipboard: {e}")),
+            Self::Web(web) if web.pasted.is_empty() => Err(anyhow!(
+                "The browser only shares the clipboard when pasting. Press Ctrl+V instead."
+            )),
+            Self::Web(web) => Ok(web.pasted.clone()),
+            Self::Memory(content) => Ok(content.clone()),
         }
     }
 
-    pub fn set_clipboard(&mut self, content: &str) {
+    pub fn set_clipboard(&mut self, content: &str) -> anyhow::Result<()> {
         match self {
-            Self::Desktop(context) => context.set_contents(content.to_owned()).unwrap(),
-            Self::Web() => unimplemented!(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Desktop(context) => context
+                .set_contents(content.to_owned())
+                .map_err(|e| anyhow!("Could not write text to the clipboard: {e}")),
+            Self::Web(web) => {
+                // Reads back our own text until the next paste, same as a real clipboard.
+                web.pasted = content.to_owned();
+                web.to_copy = Some(content.to_owned());
+                Ok(())
+            }
+            Self::Memory(memory) => {
+                *memory = content.to_owned();
+                Ok(())
+            }
+        }
+    }
+
+    /// Picks up text pasted into egui and hands pending copies to it. Call once per frame.
+    pub fn sync_egui(&mut self, ctx: &egui::Context) {
+        let Self::Web(web) = self else {
+            return; //other clipboards are accessed directly
+        };
+        if let Some(pasted) = ctx.input(|i| {
+            i.events.iter().rev().find_map(|e| match e {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            })
+        }) {
+            web.pasted = pasted;
+        }
+        if let Some(text) = web.to_copy.take() {
+            ctx.output_mut(|o| o.copied_text = text);
         }
     }
 }
@@ -1,19 +1,285 @@
+use anyhow::Context;
+use eframe::egui;
 use eframe::App;
 
-use crate::textfixer;
+use darkup::{profiles, RuleFile, Textfixer};
 
 mod clipboard;
+pub mod watcher;
 
 pub struct Application {
-    textfixer: textfixer::Textfixer,
+    textfixer: Textfixer,
     clipboard: clipboard::ClipboardHandler,
+    input: String,
+    watcher: Option<watcher::ClipboardWatcher>,
+    error: Option<String>,
+    rules_path: String,
+    words_path: String,
+    explain: bool,
+    profile: String,
 }
 
 impl Application {
-    pub fn new() -> Self {
-        Self {
-            textfixer: textfixer::Textfixer::default(),
-            clipboard: clipboard::ClipboardHandler::new(),
+    pub fn new(textfixer: Textfixer) -> Self {
+        let mut application = Self {
+            textfixer,
+            clipboard: clipboard::ClipboardHandler::memory(),
+            input: String::default(),
+            watcher: None,
+            error: None,
+            rules_path: String::default(),
+            words_path: String::default(),
+            explain: false,
+            profile: profiles::DEFAULT_PROFILE.to_owned(),
+        };
+        // Without a system clipboard the buttons still work, just within the application.
+        if let Some(clipboard) = application.report(clipboard::ClipboardHandler::new()) {
+            application.clipboard = clipboard;
         }
+        application
     }
+
+    /// Keeps the error around to be shown to the user, instead of aborting.
+    fn report<T>(&mut self, result: anyhow::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.error = Some(format!("{e:#}"));
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_profile(&mut self) {
+        let profile = profiles::path(&self.profile).and_then(|path| profiles::Profile::load(&path));
+        if let Some(profile) = self.report(profile) {
+            let skipped = self.textfixer.load_profile(&profile);
+            if !skipped.is_empty() {
+                self.error = Some(format!(
+                    "Skipped settings of profile \"{}\": {}",
+                    self.profile,
+                    skipped.join(" ")
+                ));
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_profile(&mut self) {
+        let saved =
+            profiles::path(&self.profile).and_then(|path| self.textfixer.profile().save(&path));
+        self.report(saved);
+    }
+
+    fn set_input(&mut self, input: String) {
+        self.input = input;
+        self.textfixer.set_string(&self.input);
+    }
+}
+
+impl App for Application {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::top("toolbar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Paste from clipboard").clicked() {
+                    let pasted = self.clipboard.get_clipboard();
+                    if let Some(pasted) = self.report(pasted) {
+                        self.set_input(pasted);
+                    }
+                }
+                if ui.button("Copy result").clicked() {
+                    let copied = self.clipboard.set_clipboard(self.textfixer.get_string());
+                    if self.report(copied).is_some() {
+                        if let Some(watcher) = &mut self.watcher {
+                            watcher.ignore(self.textfixer.get_string());
+                        }
+                    }
+                }
+                if ui.button("Clear").clicked() {
+                    self.set_input(String::default());
+                }
+                if let Some(restitched) = self.textfixer.restitched() {
+                    let descriptions = self
+                        .textfixer
+                        .column_suggestions()
+                        .iter()
+                        .filter(|s| !s.applied)
+                        .map(|s| s.description.as_str())
+                        .collect::<Vec<_>>();
+                    let restitch = ui.button("Replace with re-stitched text").on_hover_text(format!(
+                        "Some lines look out of column order. Replaces the input with its cleaned up text, normalized and without page headers and numbers, with those lines in the suggested order.\n{}",
+                        descriptions.join("\n")
+                    ));
+                    if restitch.clicked() {
+                        self.set_input(restitched.to_owned());
+                    }
+                }
+                ui.separator();
+                ui.checkbox(&mut self.explain, "Explain").on_hover_text(
+                    "Show which characters were normalized and which rule was applied to each linebreak, instead of the result.",
+                );
+                let mut watching = self.watcher.is_some();
+                let toggle = ui.checkbox(&mut watching, "Watch clipboard").on_hover_text(
+                    "Fix text as soon as it is copied, and put the result back on the clipboard.",
+                );
+                if toggle.changed() {
+                    self.watcher =
+                        watching.then(|| watcher::ClipboardWatcher::new(&mut self.clipboard));
+                }
+            });
+        });
+
+        if let Some(error) = self.error.clone() {
+            egui::TopBottomPanel::bottom("error").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                    if ui.button("Dismiss").clicked() {
+                        self.error = None;
+                    }
+                });
+            });
+        }
+
+        egui::SidePanel::left("settings").show(ctx, |ui| {
+            ui.heading("Settings");
+            ui.separator();
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.horizontal(|ui| {
+                    let mut picked = None;
+                    egui::ComboBox::from_id_source("profile")
+                        .selected_text("Profile")
+                        .show_ui(ui, |ui| {
+                            let names = profiles::list();
+                            for name in self.report(names).unwrap_or_default() {
+                                if ui.selectable_label(name == self.profile, &name).clicked() {
+                                    picked = Some(name);
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Switch to a saved profile.");
+                    if let Some(name) = picked {
+                        self.profile = name;
+                        self.load_profile();
+                    }
+                    ui.add(egui::TextEdit::singleline(&mut self.profile).desired_width(100.0));
+                    if ui
+                        .button("Save")
+                        .on_hover_text("Save the settings under this profile name.")
+                        .clicked()
+                    {
+                        self.save_profile();
+                    }
+                });
+                ui.separator();
+            }
+            self.textfixer.egui_render_settings(ui);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.separator();
+                ui.heading("Rules");
+                ui.add(egui::TextEdit::singleline(&mut self.rules_path).hint_text("rules.toml"))
+                    .on_hover_text(
+                        "Rule file that extends or replaces the default linebreak rules.",
+                    );
+                ui.horizontal(|ui| {
+                    if ui.button("Load").clicked() {
+                        let path = std::path::PathBuf::from(&self.rules_path);
+                        let rule_file = RuleFile::load(&path);
+                        if let Some(rule_file) = self.report(rule_file) {
+                            self.textfixer.apply_rule_file(rule_file);
+                        }
+                    }
+                    if ui.button("Reset").clicked() {
+                        self.textfixer.reset_rules();
+                    }
+                });
+
+                ui.add(egui::TextEdit::singleline(&mut self.words_path).hint_text("words.txt"))
+                    .on_hover_text("Word list, one word per line, used to decide which hyphens at line ends belong to compound words.");
+                if ui.button("Add words").clicked() {
+                    let list = std::fs::read_to_string(&self.words_path)
+                        .with_context(|| format!("Failed to read word list {}.", self.words_path));
+                    if let Some(list) = self.report(list) {
+                        self.textfixer.extend_dictionary(&list);
+                    }
+                }
+            }
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.columns(2, |columns| {
+                columns[0].label("Input");
+                egui::ScrollArea::vertical()
+                    .id_source("input")
+                    .show(&mut columns[0], |ui| {
+                        let edit = ui.add_sized(
+                            ui.available_size(),
+                            egui::TextEdit::multiline(&mut self.input)
+                                .hint_text("Paste rulebook text here."),
+                        );
+                        if edit.changed() {
+                            self.textfixer.set_string(&self.input);
+                        }
+                    });
+
+                columns[1].label("Output");
+                egui::ScrollArea::vertical()
+                    .id_source("output")
+                    .show(&mut columns[1], |ui| {
+                        // A &str buffer keeps the output selectable but read-only.
+                        let explanation;
+                        let mut output = if self.explain {
+                            explanation = self.textfixer.explain();
+                            explanation.as_str()
+                        } else {
+                            self.textfixer.get_string()
+                        };
+                        ui.add_sized(ui.available_size(), egui::TextEdit::multiline(&mut output));
+                    });
+            });
+        });
+
+        if let Some(watcher) = &mut self.watcher {
+            match watcher.poll(&mut self.clipboard, &mut self.textfixer) {
+                Ok(Some(raw)) => self.input = raw,
+                Ok(None) => {}
+                Err(e) => self.error = Some(format!("{e:#}")),
+            }
+            ctx.request_repaint_after(watcher::POLL_INTERVAL);
+        }
+
+        self.clipboard.sync_egui(ctx);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run(textfixer: Textfixer, profile: String) -> anyhow::Result<()> {
+    eframe::run_native(
+        "Darkup",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| {
+            let mut application = Application::new(textfixer);
+            application.profile = profile;
+            Box::new(application)
+        }),
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to start the application: {e}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn run_web() {
+    wasm_bindgen_futures::spawn_local(async {
+        eframe::WebRunner::new()
+            .start(
+                "darkup_canvas",
+                eframe::WebOptions::default(),
+                Box::new(|_cc| Box::new(Application::new(Textfixer::default()))),
+            )
+            .await
+            .expect("Failed to start the application.");
+    });
 }
@@ -0,0 +1,95 @@
+use super::clipboard::ClipboardHandler;
+use darkup::Textfixer;
+
+pub const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Fixes text as soon as it shows up on the clipboard, and writes the result back.
+pub struct ClipboardWatcher {
+    // Whatever was on the clipboard after the last poll. Since this includes our own output,
+    // fixed text is never picked up and processed a second time.
+    last_seen: String,
+    // Whether the last poll failed, so a clipboard that holds an image for a while
+    // only reports its error once.
+    failing: bool,
+}
+
+impl ClipboardWatcher {
+    /// Starts watching, treating whatever is currently on the clipboard as already seen.
+    pub fn new(clipboard: &mut ClipboardHandler) -> Self {
+        Self {
+            last_seen: clipboard.get_clipboard().unwrap_or_default(),
+            failing: false,
+        }
+    }
+
+    /// Marks text we put on the clipboard ourselves, so it isn't fixed again.
+    pub fn ignore(&mut self, content: &str) {
+        self.last_seen = content.to_owned();
+    }
+
+    /// Checks the clipboard once. Returns the raw text if new text was found and fixed.
+    pub fn poll(
+        &mut self,
+        clipboard: &mut ClipboardHandler,
+        textfixer: &mut Textfixer,
+    ) -> anyhow::Result<Option<String>> {
+        let content = match clipboard.get_clipboard() {
+            Ok(content) => content,
+            Err(e) if self.failing => return Ok(None),
+            Err(e) => {
+                self.failing = true;
+                return Err(e);
+            }
+        };
+        self.failing = false;
+        if content == self.last_seen || content.trim().is_empty() {
+            return Ok(None);
+        }
+
+        textfixer.set_string(&content);
+        let fixed = textfixer.get_string().to_owned();
+        if fixed != content {
+            clipboard.set_clipboard(&fixed)?;
+        }
+        self.last_seen = fixed;
+        Ok(Some(content))
+    }
+}
+
+/// Runs the watcher until the process is killed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_headless(textfixer: &mut Textfixer) -> anyhow::Result<()> {
+    let mut clipboard = ClipboardHandler::new()?;
+    let mut watcher = ClipboardWatcher::new(&mut clipboard);
+    eprintln!("Watching the clipboard. Press Ctrl+C to stop.");
+    loop {
+        match watcher.poll(&mut clipboard, textfixer) {
+            Ok(Some(raw)) => eprintln!("Fixed {} lines.", raw.lines().count()),
+            Ok(None) => {}
+            Err(e) => eprintln!("{e:#}"),
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn fixes_new_text_once() {
+        let mut clipboard = ClipboardHandler::memory();
+        let mut textfixer = Textfixer::default();
+        let mut watcher = ClipboardWatcher::new(&mut clipboard);
+        assert_eq!(watcher.poll(&mut clipboard, &mut textfixer).unwrap(), None);
+
+        clipboard
+            .set_clipboard("Text with conti-\nnuation")
+            .unwrap();
+        let raw = watcher.poll(&mut clipboard, &mut textfixer).unwrap();
+        assert_eq!(raw.as_deref(), Some("Text with conti-\nnuation"));
+        assert_eq!(clipboard.get_clipboard().unwrap(), "Text with continuation");
+
+        // Our own output must not be picked up again.
+        assert_eq!(watcher.poll(&mut clipboard, &mut textfixer).unwrap(), None);
+    }
+}
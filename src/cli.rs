@@ -0,0 +1,333 @@
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::application::watcher;
+use darkup::{
+    profiles, ColumnRepair, Confidence, Options, PageHeaders, RuleFile, SettingType, Textfixer,
+    Value, Widget,
+};
+
+pub fn command() -> Command {
+    let mut command = Command::new("darkup")
+        .about("Fixes the text formatting of World of Darkness rule books, and replaces it with Markdown where possible.")
+        .arg(
+            Arg::new("input")
+                .value_name("FILE")
+                .num_args(0..)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Files to fix. Reads from stdin if none are given."),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("DIR")
+                .value_parser(clap::value_parser!(PathBuf))
+                .requires("input")
+                .help("Write each fixed file to DIR/<name>.md instead of stdout."),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .value_name("NAME")
+                .help(format!("Settings profile saved from the application, overridden by setting flags. Profiles are kept in {}. [default: {}, if saved]", profiles::dir().map_or("the user config directory".to_owned(), |d| d.display().to_string()), profiles::DEFAULT_PROFILE)),
+        )
+        .arg(
+            Arg::new("rules")
+                .long("rules")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("TOML file with linebreak rules that extend or replace the default ones."),
+        )
+        .arg(
+            Arg::new("words")
+                .long("words")
+                .value_name("FILE")
+                .action(ArgAction::Append)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Word list, one word per line, used to decide which hyphens at line ends belong to compound words."),
+        )
+        .arg(
+            Arg::new("chapter-titles")
+                .long("chapter-titles")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Known chapter titles, one per line. These always become top level headings."),
+        )
+        .arg(
+            Arg::new("page-headers")
+                .long("page-headers")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Known running headers and footers of the book, one per line, to remove."),
+        )
+        .arg(
+            Arg::new("dump-rules")
+                .long("dump-rules")
+                .action(ArgAction::SetTrue)
+                .exclusive(true)
+                .help("Print the default linebreak rules as a rule file, as a starting point for your own."),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("output")
+                .help("Instead of the fixed text, print which characters were normalized and which rule was applied to each linebreak."),
+        )
+        .arg(
+            Arg::new("gui")
+                .long("gui")
+                .action(ArgAction::SetTrue)
+                .help("Start the graphical application. This is the default when run without arguments from a terminal."),
+        )
+        .arg(
+            Arg::new("watch")
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["input", "gui", "explain"])
+                .help("Keep running, and fix text on the clipboard as soon as it is copied."),
+        );
+
+    for setting in Options::default().settings().iter() {
+        let flag = setting.flag();
+        let arg = Arg::new(flag.clone()).long(flag.clone());
+        command = match (&setting.widget, &setting.value) {
+            // Switches get an on and an off flag, so scripts can be explicit either way.
+            (Widget::Checkbox, value) => {
+                let default = if *value == Value::Bool(true) {
+                    "on"
+                } else {
+                    "off"
+                };
+                command
+                    .arg(
+                        arg.action(ArgAction::SetTrue)
+                            .help(format!("{} [default: {default}]", setting.explanation)),
+                    )
+                    .arg(
+                        Arg::new(format!("no-{flag}"))
+                            .long(format!("no-{flag}"))
+                            .action(ArgAction::SetTrue)
+                            .conflicts_with(flag.clone())
+                            .help(format!("Disable --{flag}.")),
+                    )
+            }
+            (Widget::Choice(options), Value::Text(default)) => command.arg(
+                arg.value_name("OPTION")
+                    .value_parser(options.clone())
+                    .help(format!("{} [default: {default}]", setting.explanation)),
+            ),
+            (Widget::Slider(range), Value::Number(default)) => command.arg(
+                arg.value_name("NUMBER")
+                    .value_parser(
+                        clap::value_parser!(u64).range(*range.start() as u64..=*range.end() as u64),
+                    )
+                    .help(format!("{} [default: {default}]", setting.explanation)),
+            ),
+            (_, default) => command.arg(
+                arg.value_name("TEXT")
+                    .help(format!("{} [default: {default:?}]", setting.explanation)),
+            ),
+        };
+    }
+    command
+}
+
+pub fn wants_gui(matches: &ArgMatches) -> bool {
+    matches.get_flag("gui") || (std::env::args_os().len() == 1 && std::io::stdin().is_terminal())
+}
+
+/// Sets up a textfixer with the settings and rules given on the command line.
+pub fn textfixer(matches: &ArgMatches) -> anyhow::Result<Textfixer> {
+    let mut textfixer = Textfixer::default();
+    load_profile(&mut textfixer, matches)?;
+    apply_setting_flags(&mut textfixer, matches)?;
+    if let Some(path) = matches.get_one::<PathBuf>("rules") {
+        textfixer.apply_rule_file(RuleFile::load(path)?);
+    }
+    if let Some(path) = matches.get_one::<PathBuf>("chapter-titles") {
+        let mut heading_levels = textfixer.heading_levels().clone();
+        heading_levels.chapter_titles = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read chapter titles {}.", path.display()))?;
+        textfixer.set_heading_levels(heading_levels);
+    }
+    if let Some(path) = matches.get_one::<PathBuf>("page-headers") {
+        let known = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read page headers {}.", path.display()))?;
+        textfixer.set_page_headers(PageHeaders { known });
+    }
+    for path in matches.get_many::<PathBuf>("words").unwrap_or_default() {
+        let list = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read word list {}.", path.display()))?;
+        textfixer.extend_dictionary(&list);
+    }
+    Ok(textfixer)
+}
+
+pub fn run(matches: &ArgMatches) -> anyhow::Result<()> {
+    if matches.get_flag("dump-rules") {
+        return write_stdout(&RuleFile::defaults().to_toml()?);
+    }
+
+    let mut textfixer = textfixer(matches)?;
+
+    if matches.get_flag("watch") {
+        return watcher::run_headless(&mut textfixer);
+    }
+
+    let inputs = matches
+        .get_many::<PathBuf>("input")
+        .unwrap_or_default()
+        .collect::<Vec<_>>();
+
+    if inputs.is_empty() {
+        let mut instring = String::default();
+        std::io::stdin()
+            .read_to_string(&mut instring)
+            .context("Failed to read from stdin.")?;
+        textfixer.set_string(&instring);
+        warn_columns(&textfixer, "stdin");
+        return write_stdout(&result(&textfixer, matches));
+    }
+
+    let output_dir = matches.get_one::<PathBuf>("output");
+    if let Some(dir) = output_dir {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create output directory {}.", dir.display()))?;
+    }
+
+    for (i, input) in inputs.iter().enumerate() {
+        let instring = std::fs::read_to_string(input)
+            .with_context(|| format!("Failed to read {}.", input.display()))?;
+        textfixer.set_string(&instring);
+        warn_columns(&textfixer, &input.display().to_string());
+
+        if let Some(dir) = output_dir {
+            let path = output_path(dir, input);
+            std::fs::write(&path, textfixer.get_string())
+                .with_context(|| format!("Failed to write {}.", path.display()))?;
+        } else {
+            if i > 0 {
+                write_stdout("\n\n")?;
+            }
+            write_stdout(&result(&textfixer, matches))?;
+        }
+    }
+    Ok(())
+}
+
+fn result(textfixer: &Textfixer, matches: &ArgMatches) -> String {
+    if matches.get_flag("explain") {
+        textfixer.explain()
+    } else {
+        textfixer.get_string().to_owned()
+    }
+}
+
+/// Points out lines that look out of column order but were left alone.
+fn warn_columns(textfixer: &Textfixer, source: &str) {
+    let mode = textfixer
+        .settings()
+        .choice::<ColumnRepair>(SettingType::ColumnRepair);
+    for suggestion in textfixer.column_suggestions().iter().filter(|s| !s.applied) {
+        // Reordering only ever applies the suggestions it is sure about.
+        let advice = match (suggestion.confidence, mode) {
+            (Confidence::High, ColumnRepair::Suggest) => {
+                "Check the order of those lines, or try --column-repair reorder."
+            }
+            _ => "The signs are too weak to reorder them, check the order of those lines.",
+        };
+        eprintln!("Warning: {source}: {} {advice}", suggestion.description);
+    }
+}
+
+/// Name of the profile asked for with --profile, or the default one.
+pub fn profile_name(matches: &ArgMatches) -> &str {
+    matches
+        .get_one::<String>("profile")
+        .map_or(profiles::DEFAULT_PROFILE, String::as_str)
+}
+
+fn load_profile(textfixer: &mut Textfixer, matches: &ArgMatches) -> anyhow::Result<()> {
+    let name = profile_name(matches);
+    let path = profiles::path(name);
+    // The default profile is optional, one asked for by name isn't.
+    if !matches.contains_id("profile") && !path.as_ref().is_ok_and(|p| p.exists()) {
+        return Ok(());
+    }
+    let profile = profiles::Profile::load(&path?)?;
+    for skipped in textfixer.load_profile(&profile) {
+        eprintln!("Warning: skipped a setting of profile \"{name}\". {skipped}");
+    }
+    Ok(())
+}
+
+fn apply_setting_flags(textfixer: &mut Textfixer, matches: &ArgMatches) -> anyhow::Result<()> {
+    for setting in Options::default().settings().iter() {
+        let flag = setting.flag();
+        let value = match setting.widget {
+            Widget::Checkbox if matches.get_flag(&flag) => Value::Bool(true),
+            Widget::Checkbox if matches.get_flag(&format!("no-{flag}")) => Value::Bool(false),
+            Widget::Checkbox => continue,
+            Widget::Slider(_) => match matches.get_one::<u64>(&flag) {
+                Some(number) => Value::Number(*number as usize),
+                None => continue,
+            },
+            Widget::Choice(_) | Widget::TextField => match matches.get_one::<String>(&flag) {
+                Some(text) => Value::Text(text.clone()),
+                None => continue,
+            },
+        };
+        textfixer
+            .set_setting_value(setting.kind, value)
+            .with_context(|| format!("Invalid --{flag}."))?;
+    }
+    Ok(())
+}
+
+fn output_path(dir: &Path, input: &Path) -> PathBuf {
+    let stem = input.file_stem().unwrap_or(input.as_os_str());
+    dir.join(stem).with_extension("md")
+}
+
+fn write_stdout(content: &str) -> anyhow::Result<()> {
+    std::io::stdout()
+        .write_all(content.as_bytes())
+        .context("Failed to write to stdout.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn setting_flags() {
+        let matches = command().get_matches_from([
+            "darkup",
+            "--no-smart-hyphen-removal",
+            "--simplified-headings",
+            "--dot-style",
+            "number",
+            "--max-heading-length",
+            "60",
+        ]);
+        let mut textfixer = Textfixer::default();
+        apply_setting_flags(&mut textfixer, &matches).unwrap();
+        assert!(!textfixer.settings().check(SettingType::SmartHyphenRemoval));
+        assert!(textfixer.settings().check(SettingType::SimplifiedHeadings));
+        assert!(textfixer
+            .settings()
+            .check(SettingType::MarkdownSectionHeadings));
+        assert_eq!(textfixer.settings().text(SettingType::DotStyle), "number");
+        assert_eq!(
+            textfixer.settings().number(SettingType::MaxHeadingLength),
+            60
+        );
+        assert!(command()
+            .try_get_matches_from(["darkup", "--max-heading-length", "5"])
+            .is_err());
+    }
+}
@@ -0,0 +1,34 @@
+//! Turns text copied from World of Darkness rulebook PDFs into Markdown: spurious linebreaks and
+//! hyphens are removed, and headings, lists, dot ratings and stat blocks are marked up.
+//!
+//! ```
+//! use darkup::{Options, SettingType};
+//!
+//! let options = Options::default().with_setting(SettingType::MarkdownSectionHeadings, false)?;
+//! let output = darkup::fix("one of the most fear-\nsome tactics", &options);
+//! assert_eq!(output.text, "one of the most fearsome tactics");
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! [`Textfixer`] keeps the input around and fixes it again whenever the options change, which
+//! suits interactive use. Without the default `gui` feature the library builds without eframe
+//! and the clipboard.
+
+mod textfixer;
+
+pub use textfixer::columns::{ColumnRepair, Confidence, Suggestion};
+pub use textfixer::defaults::SettingType;
+pub use textfixer::dictionary::Verdict;
+pub use textfixer::headers::{HeadingLevels, HeadingStyle};
+pub use textfixer::linebreaks::{Join, Rule};
+pub use textfixer::normalize::{Change, Report};
+pub use textfixer::pages::PageHeaders;
+pub use textfixer::profiles::{self, Profile};
+pub use textfixer::ratings::DotStyle;
+pub use textfixer::rulefile::{self, RuleFile};
+pub use textfixer::settings::{Choice, Setting, SettingList, Value, Widget};
+pub use textfixer::statblocks::StatBlockStyle;
+pub use textfixer::texthelpers::{
+    Action, Bullet, Case, Filler, Match, PunctuationKind, SymbolPredicate, WordMatch,
+};
+pub use textfixer::{fix, Options, Output, Textfixer};
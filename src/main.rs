@@ -1,3 +1,21 @@
 mod application;
-mod textfixer;
-fn main() {}
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> anyhow::Result<()> {
+    let matches = cli::command().get_matches();
+    if cli::wants_gui(&matches) {
+        application::run(
+            cli::textfixer(&matches)?,
+            cli::profile_name(&matches).to_owned(),
+        )
+    } else {
+        cli::run(&matches)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    application::run_web();
+}
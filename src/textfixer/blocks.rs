@@ -0,0 +1,36 @@
+/// A line of the text, tagged with what it is by the stages that recognised it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    /// A section heading, already marked up if Markdown headings are enabled.
+    Heading { level: usize, text: String },
+    /// A clarifier split off a heading, like "(Clan Brujah)".
+    Clarifier(String),
+    /// Running text, which may be joined with the lines around it.
+    Paragraph(String),
+    /// The start of a list item. Running text following it may be joined onto it.
+    ListItem(String),
+    /// Text that goes into the output as is, on a line of its own.
+    Raw(String),
+}
+
+impl Block {
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Heading { text, .. }
+            | Self::Clarifier(text)
+            | Self::Paragraph(text)
+            | Self::ListItem(text)
+            | Self::Raw(text) => text,
+        }
+    }
+
+    /// Whether the linebreak before this block is up to the ruleset.
+    pub fn joins_previous(&self) -> bool {
+        matches!(self, Self::Paragraph(_))
+    }
+
+    /// Whether the linebreak after this block is up to the ruleset.
+    pub fn joins_following(&self) -> bool {
+        matches!(self, Self::Paragraph(_) | Self::ListItem(_))
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use super::dictionary::{self, Dictionary, Verdict};
+use super::texthelpers::{Match, PunctuationKind, WordMatch};
+use super::{defaults, settings};
+use defaults::SettingType as ST;
+use settings::{Choice, SettingList as SL};
+
+/// Most lines between two signs of interleaved columns for them to count as the same stretch.
+const MAX_VOTE_GAP: usize = 8;
+/// Signs of interleaved columns needed before reordering without asking.
+const CONFIDENT_VOTES: usize = 3;
+/// Most lines between a sentence broken off and the line picking it up again.
+const MAX_DISPLACED_LINES: usize = 60;
+
+/// What to do about lines copied out of column order.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum ColumnRepair {
+    /// Leave them, without looking for them.
+    Off,
+    /// Offer the input in the right order.
+    #[default]
+    Suggest,
+    /// Reorder them when the signs are clear.
+    Reorder,
+}
+
+impl Choice for ColumnRepair {
+    const ALL: &'static [(Self, &'static str)] = &[
+        (ColumnRepair::Off, "off"),
+        (ColumnRepair::Suggest, "suggest"),
+        (ColumnRepair::Reorder, "reorder"),
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Confidence {
+    Low,
+    High,
+}
+
+/// Lines that look like they were copied out of column order, and the order they belong in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub lines: Range<usize>,
+    /// Indices of the lines in `lines`, in the suggested order.
+    pub order: Vec<usize>,
+    pub confidence: Confidence,
+    pub description: String,
+    /// Whether the lines were reordered, or only suggested to be.
+    pub applied: bool,
+}
+
+/// Notices lines of two-column pages copied out of order, either interleaved line by line, or
+/// with one column dropped into the middle of a sentence of the other. Depending on the settings
+/// they are only reported, or reordered when the signs are clear.
+///
+/// Lines are only ever moved within a suggestion, so the suggestions that weren't applied still
+/// fit the returned lines.
+pub fn apply(
+    lines: Vec<String>,
+    settings: &SL<ST>,
+    dictionary: &Dictionary,
+) -> (Vec<String>, Vec<Suggestion>) {
+    let mode = settings.choice::<ColumnRepair>(ST::ColumnRepair);
+    if mode == ColumnRepair::Off {
+        return (lines, vec![]);
+    }
+
+    let mut suggestions = detect(&lines, dictionary, mode);
+    for suggestion in &mut suggestions {
+        suggestion.applied =
+            mode == ColumnRepair::Reorder && suggestion.confidence == Confidence::High;
+    }
+    let lines = restitch(lines, suggestions.iter().filter(|s| s.applied));
+    (lines, suggestions)
+}
+
+/// Puts the lines of each suggestion in the suggested order.
+pub fn restitch<'a>(
+    mut lines: Vec<String>,
+    suggestions: impl IntoIterator<Item = &'a Suggestion>,
+) -> Vec<String> {
+    for suggestion in suggestions {
+        let reordered = suggestion
+            .order
+            .iter()
+            .map(|i| std::mem::take(&mut lines[*i]))
+            .collect::<Vec<_>>();
+        lines.splice(suggestion.lines.clone(), reordered);
+    }
+    lines
+}
+
+fn detect(lines: &[String], dictionary: &Dictionary, mode: ColumnRepair) -> Vec<Suggestion> {
+    let scorer = Scorer {
+        dictionary,
+        text_words: dictionary::text_words(lines.iter().map(String::as_str)),
+    };
+    let score = |a: usize, b: usize| match (lines.get(a), lines.get(b)) {
+        (Some(a), Some(b)) => scorer.score(a, b),
+        _ => 0,
+    };
+
+    let mut suggestions = interleaved(lines, &score);
+    let mut displaced = displaced(lines, &score, &suggestions, mode);
+    suggestions.append(&mut displaced);
+    suggestions.sort_by_key(|s| s.lines.start);
+    suggestions
+}
+
+/// Stretches where each line is continued by the line after next, rather than the next one.
+fn interleaved(lines: &[String], score: &impl Fn(usize, usize) -> i32) -> Vec<Suggestion> {
+    let mut suggestions = vec![];
+    let mut votes: Vec<usize> = vec![];
+    for i in 0..lines.len().saturating_sub(2) {
+        let blank = lines[i..=i + 2].iter().any(String::is_empty);
+        let (next, after_next) = (score(i, i + 1), score(i, i + 2));
+        let against = blank || (after_next < 0 && next >= 0) || (next >= 2 && after_next < next);
+        let vote = !blank && after_next > 0 && after_next - next >= 2;
+
+        let gap = votes.last().is_some_and(|v| i - v > MAX_VOTE_GAP);
+        if against || gap {
+            suggestions.extend(interleaved_suggestion(lines, &votes));
+            votes.clear();
+        }
+        if vote {
+            votes.push(i);
+        }
+    }
+    suggestions.extend(interleaved_suggestion(lines, &votes));
+    suggestions
+}
+
+fn interleaved_suggestion(lines: &[String], votes: &[usize]) -> Option<Suggestion> {
+    let (Some(first), Some(last)) = (votes.first(), votes.last()) else {
+        return None;
+    };
+    if votes.len() < 2 {
+        return None;
+    }
+    let range = *first..last + 3;
+    let order = range
+        .clone()
+        .step_by(2)
+        .chain(range.clone().skip(1).step_by(2));
+    Some(Suggestion {
+        description: format!(
+            "{} lines from \"{}\" look like two interleaved columns.",
+            range.len(),
+            lines[*first]
+        ),
+        order: order.collect(),
+        lines: range,
+        confidence: if votes.len() >= CONFIDENT_VOTES {
+            Confidence::High
+        } else {
+            Confidence::Low
+        },
+        applied: false,
+    })
+}
+
+/// Sentences broken off, with the rest showing up after unrelated text, like the other column.
+fn displaced(
+    lines: &[String],
+    score: &impl Fn(usize, usize) -> i32,
+    interleaved: &[Suggestion],
+    mode: ColumnRepair,
+) -> Vec<Suggestion> {
+    let mut suggestions = vec![];
+    let mut covered = 0;
+    for j in 2..lines.len() {
+        // A line picking up mid-sentence after a finished one.
+        let orphaned = lines[j].starts_with(char::is_lowercase) && score(j - 1, j) < 0;
+        if !orphaned || j < covered {
+            continue;
+        }
+        let from = j.saturating_sub(MAX_DISPLACED_LINES).max(covered);
+        let Some((continued, broken)) = (from..j - 1)
+            .map(|i| (score(i, j), i))
+            .filter(|(continued, i)| *continued > 0 && *continued > score(*i, i + 1))
+            .max()
+        else {
+            continue;
+        };
+        // A single sign is too weak to bother anyone with, unless they asked for repairs.
+        if continued < 2 && mode == ColumnRepair::Suggest {
+            continue;
+        }
+        let end = (j..lines.len().min(j + MAX_DISPLACED_LINES))
+            .find(|k| lines[*k].is_empty() || ends_sentence(&lines[*k]))
+            .map_or(lines.len(), |k| k + 1);
+        let range = broken + 1..end;
+        if interleaved
+            .iter()
+            .any(|s| s.lines.start < range.end && range.start < s.lines.end)
+        {
+            continue;
+        }
+        suggestions.push(Suggestion {
+            description: format!(
+                "\"{}\" seems to continue at \"{}\", after {} unrelated lines.",
+                lines[broken],
+                lines[j],
+                j - broken - 1
+            ),
+            order: (j..end).chain(broken + 1..j).collect(),
+            lines: range,
+            confidence: if continued >= 2 {
+                Confidence::High
+            } else {
+                Confidence::Low
+            },
+            applied: false,
+        });
+        covered = end;
+    }
+    suggestions
+}
+
+fn ends_sentence(line: &str) -> bool {
+    let last_word = line.split_whitespace().last().unwrap_or_default();
+    // "etc." may as well go on, like the linebreak rules have it.
+    WordMatch::EndsSentence.matches(last_word)
+        && !defaults::ABBREVIATIONS
+            .iter()
+            .chain(&["etc."])
+            .any(|a| a.eq_ignore_ascii_case(last_word))
+}
+
+struct Scorer<'a> {
+    dictionary: &'a Dictionary,
+    text_words: HashSet<String>,
+}
+
+impl Scorer<'_> {
+    /// How well line `b` continues line `a`, from -2 for a sentence picking up after it ended,
+    /// to 2 for a word hyphenated across the two.
+    fn score(&self, a: &str, b: &str) -> i32 {
+        let (Some(last), Some(first)) = (a.chars().last(), b.chars().next()) else {
+            return 0;
+        };
+        if Match::Punctuation(PunctuationKind::Hyphen).matches(last) {
+            let left = a[..a.len() - last.len_utf8()]
+                .rsplit(|c: char| !c.is_alphabetic())
+                .next()
+                .unwrap_or_default();
+            let right = b
+                .split(|c: char| !c.is_alphabetic())
+                .next()
+                .unwrap_or_default();
+            if left.is_empty() || right.is_empty() {
+                return 0;
+            }
+            return match self.dictionary.verdict(left, right, &self.text_words) {
+                Verdict::Joined => 2,
+                Verdict::Compound => 0,
+                Verdict::Unknown => -1,
+            };
+        }
+        let mid_sentence =
+            last.is_alphabetic() || Match::Punctuation(PunctuationKind::Continuation).matches(last);
+        if ends_sentence(a) {
+            if first.is_lowercase() {
+                -2
+            } else {
+                0
+            }
+        } else if mid_sentence && first.is_lowercase() {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_owned).collect()
+    }
+    #[test]
+    fn columns() {
+        let mut settings = defaults::setting_list();
+        let dictionary = Dictionary::default();
+        let left = "In fact, keeping oneself updated is one of the most fear-
+some tactics in an elder’s arsenal. Given that many
+elders cultivate Resources and other Backgrounds to
+levels unattainable by fledglings, combining these ad-
+vantages with modern advancements makes for a po-
+tent mixture.";
+        let right = "The blood-soaked streets of Chicago belong to the
+Sabbat now. Few Kindred dare to walk them after
+midnight, and fewer still return to tell of it. The
+Prince has sent hounds, and the hounds have not
+come back.
+Nobody speaks of it.";
+        let interleaved = lines(left)
+            .into_iter()
+            .zip(lines(right))
+            .flat_map(|(l, r)| [l, r])
+            .collect::<Vec<_>>();
+
+        let (kept, suggestions) = apply(interleaved.clone(), &settings, &dictionary);
+        assert_eq!(kept, interleaved);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].confidence, Confidence::High);
+        assert_eq!(
+            restitch(interleaved.clone(), &suggestions),
+            lines(&format!("{left}\n{right}"))
+        );
+
+        settings
+            .set_value(
+                ST::ColumnRepair,
+                settings::Value::Text("reorder".to_owned()),
+            )
+            .unwrap();
+        let (reordered, _) = apply(interleaved, &settings, &dictionary);
+        assert_eq!(reordered, lines(&format!("{left}\n{right}")));
+
+        let displaced = lines(
+            "The elders of the city keep to their ha-
+SIDEBAR: FEEDING GROUNDS
+The Rack is open to any Kindred who
+knows how to keep the Masquerade.
+vens and rarely leave them.
+Neonates are left to fend for themselves.",
+        );
+        let (reordered, suggestions) = apply(displaced, &settings, &dictionary);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(
+            reordered[..2],
+            lines("The elders of the city keep to their ha-\nvens and rarely leave them.")
+        );
+
+        let (_, suggestions) = apply(lines(left), &settings, &dictionary);
+        assert!(suggestions.is_empty());
+
+        let prose =
+            lines("The Prince and\nVitel waited with claws, fangs, etc.\nand the night went on.");
+        let (_, suggestions) = apply(prose, &settings, &dictionary);
+        assert!(suggestions.is_empty());
+
+        let single_sign =
+            lines("The Prince and\nVitel waited with fangs bared.\nand the night went on.");
+        let (_, suggestions) = apply(single_sign.clone(), &settings, &dictionary);
+        assert_eq!(suggestions[0].confidence, Confidence::Low);
+        settings
+            .set_value(
+                ST::ColumnRepair,
+                settings::Value::Text("suggest".to_owned()),
+            )
+            .unwrap();
+        let (_, suggestions) = apply(single_sign, &settings, &dictionary);
+        assert!(suggestions.is_empty());
+    }
+}
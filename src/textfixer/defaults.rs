@@ -1,12 +1,37 @@
-use crate::textfixer::{linebreaks, settings, texthelpers};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone, Copy)]
+use super::{columns, headers, linebreaks, ratings, settings, statblocks, texthelpers};
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum SettingType {
     SmartHyphenRemoval,
     MarkdownSectionHeadings,
     MarkdownSubheadings,
     SimplifiedHeadings,
     SeparateHeadingClarifiers,
+    DictionaryHyphens,
+    HeadingLevels,
+    DotRatings,
+    StripPageHeaders,
+    StripPageNumbers,
+    MarkdownEscaping,
+    HeadingStyle,
+    MaxHeadingLength,
+    ClarifierWrapper,
+    BulletCharacter,
+    DotStyle,
+    StatBlocks,
+    StatBlockStyle,
+    Blockquotes,
+    Ligatures,
+    SoftHyphens,
+    ZeroWidthCharacters,
+    NonBreakingSpaces,
+    LineSeparators,
+    ColumnRepair,
+    DropCaps,
+    SmallCaps,
 }
 
 pub fn setting_list() -> settings::SettingList<SettingType> {
@@ -19,6 +44,18 @@ pub fn setting_list() -> settings::SettingList<SettingType> {
                 "Leave hyphens if they are part of a proper noun.",
                 true,
             ),
+            settings::Setting::new(
+                ST::StripPageHeaders,
+                "Strip Page Headers",
+                "Remove running headers and footers that repeat every page, and known ones.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::StripPageNumbers,
+                "Strip Page Numbers",
+                "Remove lines holding nothing but a page number.",
+                true,
+            ),
             settings::Setting::new(
                 ST::MarkdownSectionHeadings,
                 "Section Headings",
@@ -31,6 +68,18 @@ pub fn setting_list() -> settings::SettingList<SettingType> {
                 "Guess at and mark subheadings using Markdown.",
                 true,
             ),
+            settings::Setting::new(
+                ST::HeadingLevels,
+                "Heading Levels",
+                "Tell chapters, sections and sub-sections apart, instead of using a single heading level.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::MarkdownEscaping,
+                "Escape Markdown",
+                "Escape characters from the source text that Markdown would mistake for formatting.",
+                true,
+            ),
             settings::Setting::new(
                 ST::SimplifiedHeadings,
                 "Simplified Headings",
@@ -43,6 +92,110 @@ pub fn setting_list() -> settings::SettingList<SettingType> {
                 "Strips heading clarifiers (in parantheses), and turns them into subheadings.",
                 true,
             ),
+            settings::Setting::new(
+                ST::DotRatings,
+                "Dot Ratings",
+                "Tell trait ratings like Strength ••• apart from bullet points, and write them consistently.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::StatBlocks,
+                "Stat Blocks",
+                "Turn trait lists like Attributes: Strength 2, Dexterity 3 and columns of dot ratings into tables.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::Blockquotes,
+                "Block Quotes",
+                "Turn quotes and epigraphs followed by an attribution like — Author into block quotes.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::DropCaps,
+                "Drop Caps",
+                "Rejoin decorative first letters of chapters with the rest of their word, like T⏎he night.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::SmallCaps,
+                "Small Caps Openings",
+                "Write the small capitals chapters open with, like THE NIGHT WAS cold, in regular case.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::DictionaryHyphens,
+                "Dictionary Hyphens",
+                "Keep hyphens of compound words like blood-soaked, using a word list.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::Ligatures,
+                "Expand Ligatures",
+                "Replace ligatures like ﬁ and ﬂ with the separate letters.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::SoftHyphens,
+                "Remove Soft Hyphens",
+                "Remove invisible soft hyphens inside lines. The ones ending a line join the word broken there.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::ZeroWidthCharacters,
+                "Remove Zero Width Characters",
+                "Remove zero width spaces, joiners and byte order marks.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::NonBreakingSpaces,
+                "Replace Non-Breaking Spaces",
+                "Replace non-breaking and narrow spaces with regular ones.",
+                true,
+            ),
+            settings::Setting::new(
+                ST::LineSeparators,
+                "Split Line Separators",
+                "Turn Unicode line and paragraph separators into regular linebreaks.",
+                true,
+            ),
+            settings::Setting::choice::<headers::HeadingStyle>(
+                ST::HeadingStyle,
+                "Heading Style",
+                "How section headings are marked: atx writes \"## Heading\", setext underlines first and second level headings with = and -.",
+            ),
+            settings::Setting::number(
+                ST::MaxHeadingLength,
+                "Max Heading Length",
+                "Lines longer than this many characters aren't taken for headings.",
+                10..=200,
+                40,
+            ),
+            settings::Setting::text(
+                ST::ClarifierWrapper,
+                "Clarifier Wrapper",
+                "How separate heading clarifiers are marked, {} stands for the clarifier.",
+                "**{}**",
+            ),
+            settings::Setting::choice::<texthelpers::Bullet>(
+                ST::BulletCharacter,
+                "Bullet Character",
+                "Character starting the list items made from bullet points.",
+            ),
+            settings::Setting::choice::<ratings::DotStyle>(
+                ST::DotStyle,
+                "Dot Style",
+                "How dot ratings are written: dots as printed (●●●), a number (3), or circles padded to the full scale (●●●○○).",
+            ),
+            settings::Setting::choice::<statblocks::StatBlockStyle>(
+                ST::StatBlockStyle,
+                "Stat Block Style",
+                "Write stat blocks as Markdown tables, or as definition lists.",
+            ),
+            settings::Setting::choice::<columns::ColumnRepair>(
+                ST::ColumnRepair,
+                "Column Repair",
+                "Lines of two-column pages copied out of order: leave them (off), offer the input in the right order (suggest), or reorder them when the signs are clear (reorder).",
+            ),
         ],
     }
 }
@@ -57,49 +210,108 @@ pub fn ruleset() -> Vec<linebreaks::Rule> {
     use TH::Match::*;
     use TH::PunctuationKind::*;
     use TH::SymbolPredicate as SP;
+    use TH::WordMatch::*;
 
     vec![
         // Taking care of the most common and obvious spurious linebreaks.
         //
         R {
-            // Text⏎and more text
+            name: "Text⏎and more text".to_owned(),
             setting: None,
             previous: SP::new(Letter(Lowercase), Leave),
             following: SP::new(Letter(Anycase), Leave),
             filler: Filler::Space,
         },
         R {
-            // Text,⏎and more text
+            name: "Text,⏎and more text".to_owned(),
             setting: None,
             previous: SP::new(Punctuation(Continuation), Leave),
             following: SP::new(Letter(Anycase), Leave),
             filler: Filler::Space,
         },
         R {
-            // Text.⏎More text
+            name: "Text, e.g.⏎More text".to_owned(),
+            setting: None,
+            previous: SP::new(Punctuation(EndOfSentence), Leave).with_word(OneOf(abbreviations())),
+            following: SP::new(Letter(Anycase), Leave),
+            filler: Filler::Space,
+        },
+        R {
+            // "etc." can end a sentence too, so it only carries on into lowercase text.
+            name: "Text, etc.⏎and more text".to_owned(),
+            setting: None,
+            previous: SP::new(Punctuation(EndOfSentence), Leave)
+                .with_word(OneOf(vec!["etc.".to_owned()])),
+            following: SP::new(Letter(Lowercase), Leave),
+            filler: Filler::Space,
+        },
+        R {
+            name: "Text.⏎More text".to_owned(),
             setting: None,
             previous: SP::new(Punctuation(EndOfSentence), Leave),
             following: SP::new(Letter(Uppercase), Leave),
             filler: Filler::Linebreak,
         },
+        R {
+            name: "“Quote.”⏎More text".to_owned(),
+            setting: None,
+            previous: SP::new(Punctuation(ClosingQuote), Leave).with_word(EndsSentence),
+            following: SP::new(Letter(Uppercase), Leave),
+            filler: Filler::Linebreak,
+        },
+        R {
+            name: "Text “quoted”⏎and more text".to_owned(),
+            setting: None,
+            previous: SP::new(Punctuation(ClosingQuote), Leave),
+            following: SP::new(Letter(Anycase), Leave),
+            filler: Filler::Space,
+        },
+        R {
+            name: "Text…⏎and more text".to_owned(),
+            setting: None,
+            previous: SP::new(Punctuation(Ellipsis), Leave),
+            following: SP::new(Letter(Lowercase), Leave),
+            filler: Filler::Space,
+        },
+        R {
+            name: "Text⏎“quoted” text".to_owned(),
+            setting: None,
+            previous: SP::new(Letter(Anycase), Leave),
+            following: SP::new(Punctuation(OpeningQuote), Leave),
+            filler: Filler::Space,
+        },
+        R {
+            name: "Text,⏎“quoted” text".to_owned(),
+            setting: None,
+            previous: SP::new(Punctuation(Continuation), Leave),
+            following: SP::new(Punctuation(OpeningQuote), Leave),
+            filler: Filler::Space,
+        },
         // Removing unnecessary hyphens
         //
         R {
-            // Text with conti-⏎nuation
+            name: "Text with blood-⏎soaked compound".to_owned(),
+            setting: Some((S::DictionaryHyphens, true)),
+            previous: SP::new(Punctuation(Hyphen), Leave).with_word(Compound),
+            following: SP::new(Letter(Lowercase), Leave),
+            filler: Filler::None,
+        },
+        R {
+            name: "Text with conti-⏎nuation".to_owned(),
             setting: None,
             previous: SP::new(Punctuation(Hyphen), Remove),
             following: SP::new(Letter(Lowercase), Leave),
             filler: Filler::None,
         },
         R {
-            // Text with Proper-⏎Noun
+            name: "Text with Proper-⏎Noun".to_owned(),
             setting: Some((S::SmartHyphenRemoval, true)),
             previous: SP::new(Punctuation(Hyphen), Leave),
             following: SP::new(Letter(Uppercase), Leave),
             filler: Filler::None,
         },
         R {
-            // Text with Proper-⏎Noun
+            name: "Text with Proper-⏎Noun".to_owned(),
             setting: Some((S::SmartHyphenRemoval, false)),
             previous: SP::new(Punctuation(Hyphen), Remove),
             following: SP::new(Letter(Uppercase), Leave),
@@ -108,64 +320,87 @@ pub fn ruleset() -> Vec<linebreaks::Rule> {
         // Dealing with unusual structures falling on linebreaks
         //
         R {
-            // This/That/⏎TheOther
+            name: "This/That/⏎TheOther".to_owned(),
             setting: None,
             previous: SP::new(Punctuation(Slash), Leave),
             following: SP::new(Letter(Anycase), Leave),
             filler: Filler::None,
         },
         R {
-            // Text (paranthetical)⏎and more text
+            name: "Text (paranthetical)⏎and more text".to_owned(),
             setting: None,
             previous: SP::new(Punctuation(Parantheses), Leave),
             following: SP::new(Letter(Anycase), Leave),
             filler: Filler::Space,
         },
         R {
-            // Text⏎(paranthetical) and more text
+            name: "Text⏎(paranthetical) and more text".to_owned(),
             setting: None,
             previous: SP::new(Letter(Anycase), Leave),
             following: SP::new(Punctuation(Parantheses), Leave),
             filler: Filler::Space,
         },
         R {
-            // Text — paranthetical —⏎and more text
+            name: "Text — paranthetical —⏎and more text".to_owned(),
             setting: None,
             previous: SP::new(Punctuation(Dash), Leave),
             following: SP::new(Letter(Anycase), Leave),
             filler: Filler::Space,
         },
         R {
-            // Text⏎— paranthetical — and more text
+            name: "Text⏎— paranthetical — and more text".to_owned(),
             setting: None,
             previous: SP::new(Letter(Anycase), Leave),
             following: SP::new(Punctuation(Dash), Leave),
             filler: Filler::Space,
         },
         R {
-            // Quote.⏎— Author
+            name: "Text – paranthetical –⏎and more text".to_owned(),
             setting: None,
+            previous: SP::new(Punctuation(EnDash), Leave),
+            following: SP::new(Letter(Anycase), Leave),
+            filler: Filler::Space,
+        },
+        R {
+            name: "Text⏎– paranthetical – and more text".to_owned(),
+            setting: None,
+            previous: SP::new(Letter(Anycase), Leave),
+            following: SP::new(Punctuation(EnDash), Leave),
+            filler: Filler::Space,
+        },
+        R {
+            name: "Quote.⏎— Author".to_owned(),
+            setting: Some((S::MarkdownEscaping, false)),
             previous: SP::new(Punctuation(EndOfSentence), Leave),
             following: SP::new(Punctuation(Dash), Remove),
             filler: Filler::Exact("\n-".to_string()),
         },
+        R {
+            // The attribution isn't a list item, so its dash is escaped.
+            name: "Quote.⏎— Author".to_owned(),
+            setting: Some((S::MarkdownEscaping, true)),
+            previous: SP::new(Punctuation(EndOfSentence), Leave),
+            following: SP::new(Punctuation(Dash), Remove),
+            filler: Filler::Exact("\n\\-".to_string()),
+        },
         // Miscellaneous replacements
         //
         R {
-            // Section.⏎•Bulletpoint
+            // A bullet starts a new list item, whatever the previous line or item ended with.
+            name: "Text⏎•Bulletpoint".to_owned(),
             setting: None,
-            previous: SP::new(Punctuation(AnyPunctuation), Leave),
+            previous: SP::new(Anymatch, Leave),
             following: SP::new(Exact('•'), Remove),
-            filler: Filler::Exact("\n-".to_string()),
-        },
-        R {
-            // Marker symbol used to stop a linebreak from being removed erroniously.
-            // '꠷' (North Indic Placeholder Mark) is used for its apt name and low
-            // probability of being found in the source text.
-            setting: None,
-            previous: SP::new(Exact('꠷'), Remove),
-            following: SP::new(Anymatch, Leave),
-            filler: Filler::Linebreak,
+            filler: Filler::Bullet,
         },
     ]
 }
+
+/// Abbreviations ending in a period, that don't end a sentence when followed by a linebreak.
+pub const ABBREVIATIONS: [&str; 13] = [
+    "e.g.", "i.e.", "cf.", "vs.", "approx.", "p.", "pp.", "ca.", "Mr.", "Mrs.", "Ms.", "Dr.", "St.",
+];
+
+fn abbreviations() -> Vec<String> {
+    ABBREVIATIONS.map(str::to_owned).to_vec()
+}
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+/// Bundled English and World of Darkness vocabulary, one word per line.
+const BUNDLED: &str = include_str!("words.txt");
+
+/// How the two halves of a word hyphenated across a linebreak relate to the known words.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    /// The joined word is known, e.g. "fear-⏎some", so the hyphen only split it.
+    Joined,
+    /// The joined word is unknown but both halves are, e.g. "blood-⏎soaked", so the hyphen belongs.
+    Compound,
+    /// Nothing conclusive, the hyphen is handled like any other.
+    Unknown,
+}
+
+/// Word list used to tell hyphenated compounds from words split at the end of a line.
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        let mut dictionary = Self {
+            words: HashSet::default(),
+        };
+        dictionary.extend(BUNDLED);
+        dictionary
+    }
+}
+
+impl Dictionary {
+    /// Adds a word list with one word per line. Lines starting with '#' are ignored.
+    pub fn extend(&mut self, list: &str) {
+        self.words.extend(
+            list.lines()
+                .map(str::trim)
+                .filter(|w| !w.is_empty() && !w.starts_with('#'))
+                .map(str::to_lowercase),
+        );
+    }
+
+    /// Whether the word, or the word it was inflected from, is known.
+    pub fn knows(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        if self.words.contains(&word) {
+            return true;
+        }
+        // Simple inflections, so the list doesn't need to hold every form.
+        const SUFFIXES: [(&str, &str); 10] = [
+            ("s", ""),
+            ("es", ""),
+            ("ies", "y"),
+            ("ed", ""),
+            ("ed", "e"),
+            ("ied", "y"),
+            ("ing", ""),
+            ("ing", "e"),
+            ("ly", ""),
+            ("er", ""),
+        ];
+        SUFFIXES.iter().any(|(suffix, replacement)| {
+            word.strip_suffix(suffix).is_some_and(|stem| {
+                stem.len() > 1 && self.words.contains(&(stem.to_owned() + replacement))
+            })
+        })
+    }
+
+    /// Decides on "left-⏎right". Words found in the text itself count as known joined words.
+    pub fn verdict(&self, left: &str, right: &str, text_words: &HashSet<String>) -> Verdict {
+        let joined = format!("{left}{right}").to_lowercase();
+        if self
+            .words
+            .contains(&format!("{left}-{right}").to_lowercase())
+        {
+            Verdict::Compound
+        } else if self.knows(&joined) || text_words.contains(&joined) {
+            Verdict::Joined
+        } else if left.chars().count() > 1 && self.knows(left) && self.knows(right) {
+            Verdict::Compound
+        } else {
+            Verdict::Unknown
+        }
+    }
+}
+
+/// All unhyphenated words of a text, lowercased.
+pub fn text_words<'a>(lines: impl IntoIterator<Item = &'a str>) -> HashSet<String> {
+    lines
+        .into_iter()
+        .flat_map(|l| l.split(|c: char| !c.is_alphabetic() && c != '-'))
+        .filter(|w| !w.is_empty() && !w.contains('-'))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn verdicts() {
+        let dictionary = Dictionary::default();
+        let none = HashSet::default();
+        assert_eq!(dictionary.verdict("fear", "some", &none), Verdict::Joined);
+        assert_eq!(dictionary.verdict("ad", "vantages", &none), Verdict::Joined);
+        assert_eq!(
+            dictionary.verdict("blood", "soaked", &none),
+            Verdict::Compound
+        );
+        assert_eq!(dictionary.verdict("moon", "glow", &none), Verdict::Compound);
+        assert_eq!(dictionary.verdict("xyl", "ophone", &none), Verdict::Unknown);
+
+        let text_words = text_words(["The moonglow fades."]);
+        assert_eq!(
+            dictionary.verdict("moon", "glow", &text_words),
+            Verdict::Joined
+        );
+    }
+}
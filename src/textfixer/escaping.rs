@@ -0,0 +1,82 @@
+use super::blocks::Block;
+use super::{defaults, settings};
+use defaults::SettingType as ST;
+use settings::SettingList as SL;
+
+/// Escapes running text that Markdown renderers would read as syntax.
+///
+/// Only paragraphs are escaped, so the headings, lists and tables the earlier stages marked up
+/// are left alone. Those stages escape the source text inside their markup themselves. Runs before the linebreak stage, whose joins only add Markdown of their own.
+pub fn apply(blocks: Vec<Block>, settings: &SL<ST>) -> Vec<Block> {
+    if !settings.check(ST::MarkdownEscaping) {
+        return blocks;
+    }
+    blocks
+        .into_iter()
+        .map(|b| match b {
+            Block::Paragraph(text) => Block::Paragraph(escape(&text)),
+            other => other,
+        })
+        .collect()
+}
+
+fn escape(line: &str) -> String {
+    escape_line_start(escape_inline(line))
+}
+
+/// Escapes emphasis, code spans and table pipes, for source text that other stages put markup
+/// around, like headings and subheadings.
+pub fn escape_inline(text: &str) -> String {
+    let mut outstring = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '|') {
+            outstring.push('\\');
+        }
+        outstring.push(c);
+    }
+    outstring
+}
+
+/// Headings, blockquotes and lists are only syntax at the start of a line.
+fn escape_line_start(line: String) -> String {
+    let mut chars = line.chars();
+    let first = chars.next().unwrap_or_default();
+    let second = chars.next().unwrap_or(' ');
+
+    let block_marker = matches!(first, '#' | '>') || (matches!(first, '-' | '+') && second == ' ');
+    // A line of = or - would underline the line before it as a setext heading.
+    let underline = matches!(first, '=' | '-') && line.trim_end().chars().all(|c| c == first);
+    if block_marker || underline {
+        return format!("\\{line}");
+    }
+
+    // Ordered lists, like "1. " or "2) "
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    let mut rest = line[digits..].chars();
+    if digits > 0
+        && matches!(rest.next(), Some('.' | ')'))
+        && rest.next().is_none_or(char::is_whitespace)
+    {
+        return format!("{}\\{}", &line[..digits], &line[digits..]);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn escapes_markdown() {
+        assert_eq!(escape("A *starred* word_"), r"A \*starred\* word\_");
+        assert_eq!(escape("Blood | Bone"), r"Blood \| Bone");
+        assert_eq!(escape("#1 Rule"), r"\#1 Rule");
+        assert_eq!(escape("> Not a quote"), r"\> Not a quote");
+        assert_eq!(escape("- Not a list"), r"\- Not a list");
+        assert_eq!(escape("-2 dice"), "-2 dice");
+        assert_eq!(escape("==="), r"\===");
+        assert_eq!(escape("---"), r"\---");
+        assert_eq!(escape("-"), r"\-");
+        assert_eq!(escape("1. Not a list"), r"1\. Not a list");
+        assert_eq!(escape("1999 was a year."), "1999 was a year.");
+    }
+}
@@ -1,34 +1,176 @@
-use super::{defaults, settings};
+use super::blocks::Block;
+use super::{defaults, escaping, settings, statblocks};
 use defaults::SettingType as ST;
-use settings::SettingList as SL;
+use settings::{Choice, SettingList as SL};
 
-pub fn apply(mut lines: Vec<String>, settings: &SL<ST>) -> Vec<String> {
-    lines = headings(lines, settings);
-    lines = subheadings(lines, settings);
-    lines
+/// How section headings are marked.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum HeadingStyle {
+    /// "## Heading"
+    #[default]
+    Atx,
+    /// The heading underlined with = or -, for the first two levels.
+    Setext,
 }
 
-fn headings(lines: Vec<String>, settings: &SL<ST>) -> Vec<String> {
-    let mut outvec = vec![];
+impl Choice for HeadingStyle {
+    const ALL: &'static [(Self, &'static str)] =
+        &[(HeadingStyle::Atx, "atx"), (HeadingStyle::Setext, "setext")];
+}
+
+/// Signals used to tell chapter titles, sections and sub-sections apart.
+#[derive(Clone)]
+pub struct HeadingLevels {
+    /// Known chapter titles, one per line, matched case-insensitively. These are always level 1.
+    pub chapter_titles: String,
+    /// Levels of headings starting with certain words, one per line like "Chapter = 1", so
+    /// headings of the same kind get the same level however they are printed.
+    pub patterns: String,
+    /// Level of other headings in all caps.
+    pub all_caps: usize,
+    /// Level of other headings in title case.
+    pub title_case: usize,
+    /// Other headings longer than this many characters go one level down.
+    pub long_heading: usize,
+    /// Other headings following a blank line go one level up.
+    pub blank_line_promotes: bool,
+}
+
+impl Default for HeadingLevels {
+    fn default() -> Self {
+        Self {
+            chapter_titles: String::default(),
+            patterns: [
+                "Chapter = 1",
+                "Part = 1",
+                "Book = 1",
+                "Prologue = 1",
+                "Epilogue = 1",
+                "Appendix = 1",
+                "Clan = 3",
+                "Bloodline = 3",
+            ]
+            .join("\n"),
+            all_caps: 2,
+            title_case: 3,
+            long_heading: 30,
+            blank_line_promotes: false,
+        }
+    }
+}
+
+impl HeadingLevels {
+    /// Level of a heading, from its chapter title or pattern if it has one, or else from its
+    /// case, length and the blank line before it.
+    pub fn level(&self, heading: &str, after_blank: bool) -> usize {
+        let heading = heading.trim();
+        if self
+            .chapter_titles
+            .lines()
+            .any(|t| t.trim().to_lowercase() == heading.to_lowercase())
+        {
+            return 1;
+        }
+        if let Some(level) = self.pattern_level(heading) {
+            return level.clamp(1, 3);
+        }
+        let mut level = if upper_case(heading) {
+            self.all_caps
+        } else {
+            self.title_case
+        };
+        if after_blank && self.blank_line_promotes {
+            level = level.saturating_sub(1);
+        }
+        if heading.chars().count() > self.long_heading {
+            level += 1;
+        }
+        level.clamp(1, 3)
+    }
+
+    /// Level of the first pattern whose words start the heading, ignoring case.
+    fn pattern_level(&self, heading: &str) -> Option<usize> {
+        let heading = heading.to_lowercase();
+        self.patterns.lines().find_map(|line| {
+            let (words, level) = line.split_once('=')?;
+            let words = words.trim().to_lowercase();
+            let rest = heading.strip_prefix(words.as_str())?;
+            let whole_words = !words.is_empty() && !rest.starts_with(char::is_alphanumeric);
+            whole_words.then(|| level.trim().parse().ok()).flatten()
+        })
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn egui_render(&mut self, ui: &mut eframe::egui::Ui) -> settings::SettingUpdated {
+        use eframe::egui;
+        let mut updated = settings::SettingUpdated::NoChange;
+        let mut changed = false;
+        ui.label("Known chapter titles")
+            .on_hover_text("One per line. These always become top level headings.");
+        changed |= ui
+            .add(egui::TextEdit::multiline(&mut self.chapter_titles).desired_rows(3))
+            .changed();
+        ui.label("Heading patterns").on_hover_text(
+            "One per line, like \"Chapter = 1\". Headings starting with the words get that level, however they are printed.",
+        );
+        changed |= ui
+            .add(egui::TextEdit::multiline(&mut self.patterns).desired_rows(3))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut self.all_caps, 1..=3).text("All caps level"))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut self.title_case, 1..=3).text("Title case level"))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut self.long_heading, 10..=200).text("Long heading length"))
+            .on_hover_text("Headings longer than this many characters go one level down, unless a pattern decides their level.")
+            .changed();
+        changed |= ui
+            .checkbox(&mut self.blank_line_promotes, "Blank line promotes")
+            .on_hover_text("Headings following a blank line go one level up, unless a pattern decides their level.")
+            .changed();
+        if changed {
+            updated = settings::SettingUpdated::Updated;
+        }
+        updated
+    }
+}
+
+/// Picks out headings and subheadings, and turns the lines into blocks.
+pub fn apply(lines: Vec<String>, settings: &SL<ST>, levels: &HeadingLevels) -> Vec<Block> {
+    let blocks = headings(lines, settings, levels);
+    // Before subheadings, which would take "Attributes:" for one.
+    let blocks = statblocks::apply(blocks, settings);
+    subheadings(blocks, settings)
+}
+
+fn headings(lines: Vec<String>, settings: &SL<ST>, levels: &HeadingLevels) -> Vec<Block> {
+    let mut outvec: Vec<Block> = vec![];
     let mut line_iter = lines.into_iter();
     while let Some(mut line) = line_iter.next() {
+        let after_blank = outvec.last().is_some_and(|b| b.text().is_empty());
         let mut heading = String::default();
         let mut clarifier = String::default();
 
         if !line.chars().next().unwrap_or_default().is_ascii_uppercase() {
-            outvec.push(line);
+            outvec.push(Block::Paragraph(line));
             continue;
         }
 
         let mut in_clarifier = false;
+        let max_length = settings.number(ST::MaxHeadingLength);
 
         'build_head: while {
             let first = line.chars().next().unwrap_or_default();
             let last = line.chars().last().unwrap_or_default();
-            first.is_ascii_uppercase() & !matches!(last, '.' | ',' | '!' | '?')
+            // Only the part before a clarifier counts towards the length.
+            let head = line.split_once('(').map_or(line.as_str(), |(head, _)| head);
+            let fits = heading.chars().count() + head.trim_end().chars().count() <= max_length;
+            first.is_ascii_uppercase() & !matches!(last, '.' | ',' | '!' | '?') & fits
         } {
             if let Some((head, tail)) = line.split_once('(') {
-                if all_caps(head) {
+                if all_caps(head) || upper_case(head) {
                     if heading.is_empty() {
                         heading = head.to_owned()
                     } else {
@@ -40,7 +182,7 @@ fn headings(lines: Vec<String>, settings: &SL<ST>) -> Vec<String> {
                 } else {
                     break 'build_head;
                 }
-            } else if all_caps(&line) {
+            } else if all_caps(&line) || upper_case(&line) {
                 if heading.is_empty() {
                     heading = line
                 } else {
@@ -79,6 +221,13 @@ fn headings(lines: Vec<String>, settings: &SL<ST>) -> Vec<String> {
             }
         }
         clarifier = clarifier.trim_start_matches('(').to_owned();
+        heading.truncate(heading.trim_end().len());
+
+        let level = if settings.check(ST::HeadingLevels) {
+            levels.level(&heading, after_blank)
+        } else {
+            1
+        };
 
         if settings.check(ST::SimplifiedHeadings) {
             heading = diacritics::remove_diacritics(&heading);
@@ -87,61 +236,119 @@ fn headings(lines: Vec<String>, settings: &SL<ST>) -> Vec<String> {
             clarifier = clarifier.replace('’', " ");
         }
 
+        // Markup goes around them below, so they aren't escaped along with the paragraphs.
+        if settings.check(ST::MarkdownEscaping) {
+            heading = escaping::escape_inline(&heading);
+            clarifier = escaping::escape_inline(&clarifier);
+        }
+
         if !settings.check(ST::SeparateHeadingClarifiers) & !clarifier.is_empty() {
-            heading = format!("{heading}({clarifier})");
+            heading = format!("{heading} ({clarifier})");
             clarifier = String::default();
         }
 
         if settings.check(ST::MarkdownSectionHeadings) && !heading.is_empty() {
-            heading = "# ".to_owned() + &heading;
+            heading = markup(&heading, level, settings.choice(ST::HeadingStyle));
             if !clarifier.is_empty() {
-                clarifier = format!("**{clarifier}**");
+                clarifier = wrap(&clarifier, settings.text(ST::ClarifierWrapper));
             }
         }
 
         if !heading.is_empty() {
-            heading.push('꠷');
-            outvec.push(heading);
+            // A setext underline would take the text right above it into the heading.
+            let underlined = heading.contains('\n');
+            if underlined && outvec.last().is_some_and(|block| !block.text().is_empty()) {
+                outvec.push(Block::Raw(String::default()));
+            }
+            outvec.push(Block::Heading {
+                level,
+                text: heading,
+            });
         }
         if !clarifier.is_empty() {
-            clarifier.push('꠷');
-            outvec.push(clarifier);
+            outvec.push(Block::Clarifier(clarifier));
         }
         if !line.is_empty() {
-            outvec.push(line);
+            outvec.push(Block::Paragraph(line));
         }
     }
     outvec
 }
 
-fn subheadings(mut lines: Vec<String>, settings: &SL<ST>) -> Vec<String> {
-    lines.iter_mut().for_each(|l| {
-        let Some((mut subheading, tail)) = l
-            .split_once(':')
-            .map(|(front, back)| (front.to_owned(), back))
-        else {
-            return;
-        };
+/// Marks a heading in the given style.
+fn markup(heading: &str, level: usize, style: HeadingStyle) -> String {
+    // Setext only has underlines for the first two levels.
+    let underline = match (style, level) {
+        (HeadingStyle::Setext, 1) => '=',
+        (HeadingStyle::Setext, 2) => '-',
+        _ => return "#".repeat(level) + " " + heading,
+    };
+    let width = heading.trim_end().chars().count();
+    format!(
+        "{}\n{}",
+        heading.trim_end(),
+        underline.to_string().repeat(width)
+    )
+}
 
-        let markdown = settings.check(ST::MarkdownSubheadings);
+/// Puts a clarifier in its wrapper, where "{}" stands for the clarifier.
+/// A wrapper without one, like "*", goes on both sides.
+fn wrap(clarifier: &str, wrapper: &str) -> String {
+    if wrapper.contains("{}") {
+        wrapper.replace("{}", clarifier)
+    } else {
+        format!("{wrapper}{clarifier}{wrapper}")
+    }
+}
 
-        if subheading.chars().next().unwrap_or_default() == '•' {
-            subheading.trim_start().matches("• ");
-            subheading = format!("- {subheading}:");
-        } else if all_caps(&subheading) {
-            if markdown {
-                subheading = format!("- {subheading}:");
-            }
-        } else {
-            return;
-        };
+fn subheadings(blocks: Vec<Block>, settings: &SL<ST>) -> Vec<Block> {
+    blocks
+        .into_iter()
+        .map(|b| subheading(b, settings))
+        .collect()
+}
 
-        if markdown {
-            subheading = format!("**{subheading}**");
-        }
-        *l = subheading + tail;
-    });
-    lines
+fn subheading(block: Block, settings: &SL<ST>) -> Block {
+    let Block::Paragraph(l) = block else {
+        return block;
+    };
+    let Some((mut subheading, tail)) = l
+        .split_once(':')
+        .map(|(front, back)| (front.to_owned(), back))
+    else {
+        return Block::Paragraph(l);
+    };
+
+    let markdown = settings.check(ST::MarkdownSubheadings);
+
+    let bullet = subheading.starts_with('•');
+    if bullet {
+        subheading = subheading.trim_start_matches('•').trim_start().to_owned();
+    } else if !all_caps(&subheading) && !upper_case(&subheading) {
+        return Block::Paragraph(l);
+    }
+
+    // Plain paragraphs are escaped later on, along with the others.
+    let list_item = bullet || markdown;
+    let mut tail = tail.to_owned();
+    if list_item && settings.check(ST::MarkdownEscaping) {
+        subheading = escaping::escape_inline(&subheading);
+        tail = escaping::escape_inline(&tail);
+    }
+
+    subheading += ":";
+    if markdown {
+        subheading = format!("**{subheading}**");
+    }
+    // The list marker goes outside the emphasis, or it wouldn't start a list.
+    if list_item {
+        Block::ListItem(format!(
+            "{} {subheading}{tail}",
+            settings.text(ST::BulletCharacter)
+        ))
+    } else {
+        Block::Paragraph(subheading + &tail)
+    }
 }
 
 fn all_caps(instr: &str) -> bool {
@@ -150,3 +357,11 @@ fn all_caps(instr: &str) -> bool {
             & w.chars().last().unwrap_or_default().is_ascii_lowercase()
     })
 }
+
+fn upper_case(instr: &str) -> bool {
+    instr.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && instr
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .all(char::is_uppercase)
+}
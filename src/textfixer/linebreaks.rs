@@ -1,7 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use super::blocks::Block;
+use super::dictionary::{self, Dictionary, Verdict};
 use super::texthelpers::*;
 use super::{defaults, settings};
 
+#[derive(Serialize, Deserialize)]
 pub struct Rule {
+    /// Short example of the text the rule is meant for, e.g. "Text⏎and more text".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub setting: Option<(defaults::SettingType, bool)>,
     pub previous: SymbolPredicate,
     pub following: SymbolPredicate,
@@ -9,18 +18,32 @@ pub struct Rule {
 }
 
 impl Rule {
-    fn matches(&self, previous: char, following: char) -> bool {
-        self.previous.symbol.matches(previous) && self.following.symbol.matches(following)
+    fn matches(
+        &self,
+        previous: (char, &str),
+        following: (char, &str),
+        verdict: Option<Verdict>,
+    ) -> bool {
+        // The linebreak itself is on the other side of either symbol.
+        let before = previous.1.chars().rev().nth(1);
+        let after = following.1.chars().nth(1);
+        self.previous
+            .matches(previous.0, (before, Some('\n')), previous.1, verdict)
+            && self
+                .following
+                .matches(following.0, (Some('\n'), after), following.1, verdict)
     }
-    fn merge(&self, mut left: String, mut right: &str) -> String {
+    /// Joins `right` onto the end of `left`, in place.
+    fn merge(&self, left: &mut String, mut right: &str, list_item: &str) {
         if self.previous.on_match == Action::Remove {
             left.pop();
         }
         if self.following.on_match == Action::Remove {
-            right = &right[1..]
+            right = &right[right.chars().next().map_or(0, char::len_utf8)..]
         }
 
-        left + self.filler.get() + right
+        left.push_str(self.filler.get(list_item));
+        left.push_str(right);
     }
     fn is_enabled(&self, settings: &settings::SettingList<defaults::SettingType>) -> bool {
         if let Some((setting, enabled)) = self.setting {
@@ -31,37 +54,146 @@ impl Rule {
     }
 }
 
+/// What happened to a single linebreak, to explain how the output came to be.
+#[derive(Debug)]
+pub struct Join {
+    /// Index of the block following the linebreak, or of the block quote it was inside. Earlier
+    /// stages remove and merge lines, so this is no line number of the input.
+    pub block: usize,
+    /// End of the text before the linebreak.
+    pub before: String,
+    /// Start of the line after the linebreak.
+    pub after: String,
+    pub previous: Option<char>,
+    pub following: Option<char>,
+    /// Index of the rule that was applied, if any.
+    pub rule: Option<usize>,
+    /// Name of the rule that was applied, if any.
+    pub rule_name: String,
+    /// Setting the applied rule depends on.
+    pub setting: Option<(defaults::SettingType, bool)>,
+    /// Label of that setting, as shown in the application.
+    pub setting_label: String,
+    /// What the linebreak was replaced with.
+    pub filler: String,
+    /// What the dictionary made of a hyphen before the linebreak.
+    pub hyphen: Option<Verdict>,
+}
+
+const CONTEXT_CHARS: usize = 24;
+
 pub fn apply(
-    lines: &[String],
+    blocks: &[Block],
     ruleset: &[Rule],
     settings: &settings::SettingList<defaults::SettingType>,
-) -> String {
-    let mut line_iter = lines.iter();
-    let Some(mut outstring) = line_iter.next().cloned() else {
-        return String::default(); //input was empty
+    dictionary: &Dictionary,
+) -> (String, Vec<Join>) {
+    let mut text_words = None; //only collected once a hyphen needs deciding
+    let mut trace = Vec::with_capacity(blocks.len());
+    // Settings don't change during a fix, so the disabled rules are left out up front.
+    let enabled = ruleset
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.is_enabled(settings))
+        .collect::<Vec<_>>();
+    let list_item = format!(
+        "\n{}",
+        settings.text(defaults::SettingType::BulletCharacter)
+    );
+    let mut block_iter = blocks.iter().enumerate();
+    let Some((_, first)) = block_iter.next() else {
+        return (String::default(), trace); //input was empty
     };
+    let mut outstring = String::with_capacity(blocks.iter().map(|b| b.text().len() + 1).sum());
+    outstring.push_str(first.text());
+    let mut joins_following = first.joins_following();
+
+    for (index, block) in block_iter {
+        let line = block.text();
+        let joinable = joins_following && block.joins_previous();
+        joins_following = block.joins_following();
+        let previous_char = outstring.chars().last();
+        let following_char = line.chars().next();
+        let mut join = Join {
+            block: index,
+            before: tail(&outstring),
+            after: line.chars().take(CONTEXT_CHARS).collect(),
+            previous: previous_char,
+            following: following_char,
+            rule: None,
+            rule_name: String::default(),
+            setting: None,
+            setting_label: String::default(),
+            filler: "\n".to_owned(),
+            hyphen: None,
+        };
 
-    for line in line_iter {
-        let previous_char = outstring
-            .chars()
-            .last()
-            .expect("Outstring was empty when getting previous char.");
-        let Some(following_char) = line.chars().next() else {
-            outstring += "\n"; //line is empty, add its linebreak and move on
+        let (true, Some(previous_char), Some(following_char)) =
+            (joinable, previous_char, following_char)
+        else {
+            outstring += "\n"; //a heading or an empty line, keep the linebreak and move on
+            outstring += line;
+            trace.push(join);
             continue;
         };
 
-        if let Some(rule) = ruleset
+        let previous_word = outstring
+            .rsplit(char::is_whitespace)
+            .next()
+            .unwrap_or_default();
+        let following_word = line.split(char::is_whitespace).next().unwrap_or_default();
+        let previous = (previous_char, previous_word);
+        let following = (following_char, following_word);
+
+        // A soft hyphen only ever breaks a word, so the dictionary has no say on it.
+        if Match::Punctuation(PunctuationKind::Hyphen).matches(previous_char)
+            && previous_char != '\u{00AD}'
+            && following_char.is_alphabetic()
+        {
+            let text_words = text_words
+                .get_or_insert_with(|| dictionary::text_words(blocks.iter().map(Block::text)));
+            let left = previous_word
+                .trim_end_matches(previous_char)
+                .rsplit(|c: char| !c.is_alphabetic())
+                .next()
+                .unwrap_or_default();
+            let right = following_word
+                .split(|c: char| !c.is_alphabetic())
+                .next()
+                .unwrap_or_default();
+            join.hyphen = Some(dictionary.verdict(left, right, text_words));
+        }
+
+        if let Some((i, rule)) = enabled
             .iter()
-            .find(|r| r.matches(previous_char, following_char) && r.is_enabled(settings))
+            .find(|(_, r)| r.matches(previous, following, join.hyphen))
         {
-            outstring = rule.merge(outstring, line); //apply the matching rule
-            continue;
+            rule.merge(&mut outstring, line, &list_item); //apply the matching rule
+            join.rule = Some(*i);
+            join.rule_name = rule.name.clone();
+            join.setting = rule.setting;
+            if let Some((kind, _)) = rule.setting {
+                join.setting_label = settings.label(kind).to_owned();
+            }
+            join.filler = rule.filler.get(&list_item).to_owned();
         } else {
-            outstring = outstring + "\n" + &line; //no rule applies, add the linebreak and move on
-            continue;
+            outstring += "\n"; //no rule applies, add the linebreak and move on
+            outstring += line;
         };
+        trace.push(join);
     }
 
-    outstring
+    (outstring, trace)
+}
+
+/// Last few characters of the current line, without scanning back over the whole paragraph.
+fn tail(text: &str) -> String {
+    let mut tail = text
+        .chars()
+        .rev()
+        .take_while(|c| *c != '\n')
+        .take(CONTEXT_CHARS)
+        .collect::<Vec<_>>();
+    tail.reverse();
+    tail.into_iter().collect()
 }
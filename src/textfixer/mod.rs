@@ -1,45 +1,337 @@
-mod defaults;
-mod headers;
-mod linebreaks;
-mod settings;
-mod texthelpers;
+pub(crate) mod blocks;
+pub(crate) mod columns;
+pub(crate) mod defaults;
+pub(crate) mod dictionary;
+pub(crate) mod escaping;
+pub(crate) mod headers;
+pub(crate) mod linebreaks;
+pub(crate) mod normalize;
+pub(crate) mod openings;
+pub(crate) mod pages;
+pub mod profiles;
+pub(crate) mod quotes;
+pub(crate) mod ratings;
+pub mod rulefile;
+pub(crate) mod settings;
+pub(crate) mod statblocks;
+pub(crate) mod texthelpers;
 
-pub struct Textfixer {
-    instring: String,
-    outstring: String,
-    ruleset: Vec<linebreaks::Rule>,
+/// Settings, linebreak rules and word lists a text is fixed with, e.g.
+/// ```
+/// use darkup::{Options, SettingType};
+///
+/// let options = Options::default()
+///     .with_setting(SettingType::MarkdownSectionHeadings, false)?
+///     .with_words("Tremere\nMalkavian");
+/// assert!(!options.settings().check(SettingType::MarkdownSectionHeadings));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct Options {
     settings: settings::SettingList<defaults::SettingType>,
+    ruleset: Vec<linebreaks::Rule>,
+    dictionary: dictionary::Dictionary,
+    heading_levels: headers::HeadingLevels,
+    page_headers: pages::PageHeaders,
 }
-impl Default for Textfixer {
+impl Default for Options {
     fn default() -> Self {
         Self {
-            instring: String::default(),
-            outstring: String::default(),
-            ruleset: defaults::ruleset(),
             settings: defaults::setting_list(),
+            ruleset: defaults::ruleset(),
+            dictionary: dictionary::Dictionary::default(),
+            heading_levels: headers::HeadingLevels::default(),
+            page_headers: pages::PageHeaders::default(),
         }
     }
 }
 
+impl Options {
+    /// Enables or disables a checkbox setting. Fails for other kinds of settings.
+    pub fn with_setting(
+        mut self,
+        kind: defaults::SettingType,
+        enabled: bool,
+    ) -> anyhow::Result<Self> {
+        self.settings.set(kind, enabled)?;
+        Ok(self)
+    }
+    /// Sets a choice, number or text setting.
+    pub fn with_value(
+        mut self,
+        kind: defaults::SettingType,
+        value: settings::Value,
+    ) -> anyhow::Result<Self> {
+        self.settings.set_value(kind, value)?;
+        Ok(self)
+    }
+    /// Switches to the settings of a profile. Fails if any of them can't be used.
+    pub fn with_profile(mut self, profile: &profiles::Profile) -> anyhow::Result<Self> {
+        let skipped = profile.apply(&mut self.settings);
+        if !skipped.is_empty() {
+            anyhow::bail!("Skipped settings of the profile: {}", skipped.join(" "));
+        }
+        Ok(self)
+    }
+    /// Replaces the linebreak rules.
+    pub fn with_rules(mut self, rules: Vec<linebreaks::Rule>) -> Self {
+        self.ruleset = rules;
+        self
+    }
+    /// Combines the rules from a user rule file with the current ones.
+    pub fn with_rule_file(mut self, rule_file: rulefile::RuleFile) -> Self {
+        self.ruleset = rule_file.apply(self.ruleset);
+        self
+    }
+    /// Adds a user word list, with one word per line, to the dictionary used for hyphens.
+    pub fn with_words(mut self, list: &str) -> Self {
+        self.dictionary.extend(list);
+        self
+    }
+    pub fn with_heading_levels(mut self, heading_levels: headers::HeadingLevels) -> Self {
+        self.heading_levels = heading_levels;
+        self
+    }
+    pub fn with_page_headers(mut self, page_headers: pages::PageHeaders) -> Self {
+        self.page_headers = page_headers;
+        self
+    }
+    pub fn settings(&self) -> &settings::SettingList<defaults::SettingType> {
+        &self.settings
+    }
+    pub fn rules(&self) -> &[linebreaks::Rule] {
+        &self.ruleset
+    }
+    pub fn heading_levels(&self) -> &headers::HeadingLevels {
+        &self.heading_levels
+    }
+    pub fn page_headers(&self) -> &pages::PageHeaders {
+        &self.page_headers
+    }
+}
+
+/// Fixed text, and how it came to be.
+#[derive(Default)]
+pub struct Output {
+    pub text: String,
+    /// What happened to each linebreak.
+    pub trace: Vec<linebreaks::Join>,
+    /// How many characters each normalization changed.
+    pub normalization: normalize::Report,
+    /// Lines that look like they were copied out of column order, reordered or not.
+    pub columns: Vec<columns::Suggestion>,
+    /// The cleaned up input with the suggested column order, if there are suggestions left that
+    /// weren't applied. It is normalized, and page headers and numbers are already removed.
+    pub restitched: Option<String>,
+}
+
+impl Output {
+    /// Human readable version of the trace, one linebreak per line, after the normalizations
+    /// that changed anything and the lines out of column order.
+    pub fn explain(&self) -> String {
+        let mut explanation = self.normalization.describe();
+        for suggestion in &self.columns {
+            let action = if suggestion.applied {
+                "reordered"
+            } else {
+                "suggested to reorder"
+            };
+            explanation += &format!("{} ({action})\n", suggestion.description);
+        }
+        for join in &self.trace {
+            let rule = match join.rule {
+                Some(i) => format!("rule {i} \"{}\"", join.rule_name),
+                None => "no rule".to_owned(),
+            };
+            let gate = match join.setting {
+                Some((_, enabled)) => format!(
+                    " [{}: {}]",
+                    join.setting_label,
+                    if enabled { "on" } else { "off" }
+                ),
+                None => String::default(),
+            };
+            let hyphen = match join.hyphen {
+                Some(dictionary::Verdict::Joined) => " (dictionary: joined word)",
+                Some(dictionary::Verdict::Compound) => " (dictionary: compound word)",
+                Some(dictionary::Verdict::Unknown) => " (dictionary: unknown)",
+                None => "",
+            };
+            explanation += &format!(
+                "block {}: {:?} ⏎ {:?} -> {rule}{gate}{hyphen}, filler {:?}\n",
+                join.block + 1,
+                join.before,
+                join.after,
+                join.filler
+            );
+        }
+        explanation
+    }
+}
+
+/// Turns text copied from a rulebook into Markdown.
+pub fn fix(input: &str, options: &Options) -> Output {
+    let Options {
+        settings,
+        ruleset,
+        dictionary,
+        heading_levels,
+        page_headers,
+    } = options;
+    let (normalized, normalization) = normalize::apply(input, settings);
+    let mut lines = normalized
+        .lines()
+        .map(|l| l.trim().to_owned())
+        .collect::<Vec<_>>();
+    lines = pages::apply(lines, settings, page_headers);
+    let columns;
+    (lines, columns) = columns::apply(lines, settings, dictionary);
+    let pending = columns.iter().filter(|s| !s.applied).collect::<Vec<_>>();
+    let restitched =
+        (!pending.is_empty()).then(|| columns::restitch(lines.clone(), pending).join("\n"));
+    lines = openings::apply(lines, settings, dictionary);
+    lines = ratings::apply(lines, settings);
+    let mut blocks = headers::apply(lines, settings, heading_levels);
+    blocks = escaping::apply(blocks, settings);
+    let quote_trace;
+    (blocks, quote_trace) = quotes::apply(blocks, settings, ruleset, dictionary);
+    let (text, mut trace) = linebreaks::apply(&blocks, ruleset, settings, dictionary);
+    // Sorting is stable, so the joins inside a block quote follow the linebreak before it.
+    trace.extend(quote_trace);
+    trace.sort_by_key(|join| join.block);
+    Output {
+        text,
+        trace,
+        normalization,
+        columns,
+        restitched,
+    }
+}
+
+/// Keeps the input and the result of the last fix around, fixing again whenever the input or
+/// the options change.
+#[derive(Default)]
+pub struct Textfixer {
+    instring: String,
+    options: Options,
+    output: Output,
+}
+
 impl Textfixer {
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
     pub fn set_string(&mut self, instring: &str) {
         self.instring = instring.to_owned();
         self.fix();
     }
     pub fn get_string(&self) -> &str {
-        &self.outstring
+        &self.output.text
     }
     pub fn fix(&mut self) {
-        let mut lines = self
-            .instring
-            .lines()
-            .map(|l| l.trim().to_owned())
-            .collect::<Vec<_>>();
-        lines = headers::apply(lines, &self.settings);
-        self.outstring = linebreaks::apply(&lines, &self.ruleset, &self.settings);
+        self.output = fix(&self.instring, &self.options);
+    }
+    pub fn options(&self) -> &Options {
+        &self.options
     }
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+    /// [`Output::trace`] of the last fix.
+    pub fn trace(&self) -> &[linebreaks::Join] {
+        &self.output.trace
+    }
+    /// [`Output::normalization`] of the last fix.
+    pub fn normalization(&self) -> &normalize::Report {
+        &self.output.normalization
+    }
+    /// [`Output::columns`] of the last fix.
+    pub fn column_suggestions(&self) -> &[columns::Suggestion] {
+        &self.output.columns
+    }
+    /// [`Output::restitched`] of the last fix.
+    pub fn restitched(&self) -> Option<&str> {
+        self.output.restitched.as_deref()
+    }
+    /// [`Output::explain`] for the last fix.
+    pub fn explain(&self) -> String {
+        self.output.explain()
+    }
+    pub fn settings(&self) -> &settings::SettingList<defaults::SettingType> {
+        &self.options.settings
+    }
+    /// Like [`Options::with_setting`], and fixes again.
+    pub fn set_setting(
+        &mut self,
+        kind: defaults::SettingType,
+        enabled: bool,
+    ) -> anyhow::Result<()> {
+        self.options.settings.set(kind, enabled)?;
+        self.fix();
+        Ok(())
+    }
+    /// Like [`Options::with_value`], and fixes again.
+    pub fn set_setting_value(
+        &mut self,
+        kind: defaults::SettingType,
+        value: settings::Value,
+    ) -> anyhow::Result<()> {
+        self.options.settings.set_value(kind, value)?;
+        self.fix();
+        Ok(())
+    }
+    /// Current settings, to be saved as a profile.
+    pub fn profile(&self) -> profiles::Profile {
+        profiles::Profile::from_settings(&self.options.settings)
+    }
+    /// Switches to the settings of a profile, and describes the saved settings that were skipped.
+    pub fn load_profile(&mut self, profile: &profiles::Profile) -> Vec<String> {
+        let skipped = profile.apply(&mut self.options.settings);
+        self.fix();
+        skipped
+    }
+    /// Like [`Options::with_rule_file`], and fixes again.
+    pub fn apply_rule_file(&mut self, rule_file: rulefile::RuleFile) {
+        let ruleset = std::mem::take(&mut self.options.ruleset);
+        self.options.ruleset = rule_file.apply(ruleset);
+        self.fix();
+    }
+    /// Like [`Options::with_words`], and fixes again.
+    pub fn extend_dictionary(&mut self, list: &str) {
+        self.options.dictionary.extend(list);
+        self.fix();
+    }
+    pub fn heading_levels(&self) -> &headers::HeadingLevels {
+        &self.options.heading_levels
+    }
+    pub fn set_heading_levels(&mut self, heading_levels: headers::HeadingLevels) {
+        self.options.heading_levels = heading_levels;
+        self.fix();
+    }
+    pub fn set_page_headers(&mut self, page_headers: pages::PageHeaders) {
+        self.options.page_headers = page_headers;
+        self.fix();
+    }
+    pub fn reset_rules(&mut self) {
+        self.options.ruleset = defaults::ruleset();
+        self.fix();
+    }
+    #[cfg(feature = "gui")]
     pub fn egui_render_settings(&mut self, ui: &mut eframe::egui::Ui) {
-        let updated = self.settings.egui_render(ui);
+        let options = &mut self.options;
+        let mut updated = options.settings.egui_render(ui);
+        ui.collapsing("Page headers", |ui| {
+            if options.page_headers.egui_render(ui) == settings::SettingUpdated::Updated {
+                updated = settings::SettingUpdated::Updated;
+            }
+        });
+        ui.collapsing("Heading levels", |ui| {
+            if options.heading_levels.egui_render(ui) == settings::SettingUpdated::Updated {
+                updated = settings::SettingUpdated::Updated;
+            }
+        });
         if updated == settings::SettingUpdated::Updated {
             self.fix();
         }
@@ -65,12 +357,128 @@ elders cultivate Resources and other Backgrounds to
 levels unattainable by fledglings, combining these ad-
 vantages with modern advancements makes for a po-
 tent mixture.";
-        let lines = teststr
+        let blocks = teststr
             .lines()
-            .map(|s| s.to_owned())
-            .collect::<Vec<String>>();
-        let fixed = linebreaks::apply(&lines, &textfixer.ruleset, &textfixer.settings);
-        eprintln!("'{fixed}'");
+            .map(|s| blocks::Block::Paragraph(s.to_owned()))
+            .collect::<Vec<_>>();
+        let (fixed, _) = linebreaks::apply(
+            &blocks,
+            &textfixer.options.ruleset,
+            &textfixer.options.settings,
+            &textfixer.options.dictionary,
+        );
+        assert!(!fixed.contains('\n'));
+        assert!(fixed.contains("most fearsome tactics"));
+        assert!(fixed.contains("these advantages with"));
+        // "car-pet" would be a compound, but a soft hyphen always breaks a single word.
+        assert_eq!(fix("car\u{00AD}\npet", &Options::default()).text, "carpet");
+    }
+    #[test]
+    fn trace() {
+        let mut textfixer = Textfixer::default();
+        textfixer.set_string("fear-\nsome\n\nmore text.");
+        let trace = textfixer.trace();
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[0].previous, Some('-'));
+        assert_eq!(trace[0].following, Some('s'));
+        assert_eq!(
+            textfixer.options.ruleset[trace[0].rule.unwrap()].name,
+            trace[0].rule_name
+        );
+        assert_eq!(trace[0].rule_name, "Text with conti-⏎nuation");
+        assert_eq!(trace[0].filler, "");
+        assert_eq!(trace[1].rule, None);
+        assert_eq!(trace[2].following, Some('m'));
+        assert!(fix("fear-\nsome", &Options::default())
+            .explain()
+            .contains("\"Text with conti-⏎nuation\""));
+    }
+    #[test]
+    fn abbreviations() {
+        let mut textfixer = Textfixer::default();
+        textfixer.set_string("Some clans, e.g.\nToreador, like art. Others\ndon't.\nThey hunt.");
+        assert_eq!(
+            textfixer.get_string(),
+            "Some clans, e.g. Toreador, like art. Others don't.\nThey hunt."
+        );
+        textfixer.set_string("Blood, bone, etc.\nThey hunt with claws, fangs, etc.\nand more.");
+        assert_eq!(
+            textfixer.get_string(),
+            "Blood, bone, etc.\nThey hunt with claws, fangs, etc. and more."
+        );
+    }
+    #[test]
+    fn heading_levels() {
+        let mut textfixer = Textfixer::default();
+        textfixer.set_heading_levels(headers::HeadingLevels {
+            chapter_titles: "The Kindred".to_owned(),
+            ..Default::default()
+        });
+        textfixer.set_string("THE KINDRED\nText.\nTHE CLANS\nText.\nClan Brujah\nText.");
+        assert_eq!(
+            textfixer.get_string(),
+            "# THE KINDRED\nText.\n## THE CLANS\nText.\n### Clan Brujah\nText."
+        );
+        // Headings of the same kind get the same level, whatever is around them.
+        textfixer.set_string("Clan Brujah\nText.\n\nClan Gangrel\nText.\nCLAN NOSFERATU\nText.");
+        assert_eq!(
+            textfixer.get_string(),
+            "### Clan Brujah\nText.\n\n### Clan Gangrel\nText.\n### CLAN NOSFERATU\nText."
+        );
+        textfixer.set_heading_levels(headers::HeadingLevels {
+            blank_line_promotes: true,
+            ..Default::default()
+        });
+        textfixer.set_string("Text.\n\nTHE CLANS\nText.\nTHE CURSE\nText.");
+        assert_eq!(
+            textfixer.get_string(),
+            "Text.\n\n# THE CLANS\nText.\n## THE CURSE\nText."
+        );
+    }
+    #[test]
+    fn headings_stand_alone() {
+        let mut textfixer = Textfixer::default();
+        textfixer
+            .set_setting(defaults::SettingType::MarkdownSectionHeadings, false)
+            .unwrap();
+        textfixer.set_string("the end of a chapter\nClan Brujah\nthe start of another.");
+        assert_eq!(
+            textfixer.get_string(),
+            "the end of a chapter\nClan Brujah\nthe start of another."
+        );
+    }
+    #[test]
+    fn max_heading_length() {
+        let mut textfixer = Textfixer::default();
+        textfixer
+            .set_setting_value(
+                defaults::SettingType::MaxHeadingLength,
+                settings::Value::Number(20),
+            )
+            .unwrap();
+        textfixer.set_string(
+            "Clan Brujah\nText.\nThe Many Traditions Of The Camarilla And Their Keepers\nText.",
+        );
+        assert_eq!(
+            textfixer.get_string(),
+            "### Clan Brujah\nText.\nThe Many Traditions Of The Camarilla And Their Keepers Text."
+        );
+    }
+    #[test]
+    fn escapes_source_text_in_markup() {
+        let mut textfixer = Textfixer::default();
+        textfixer.set_string(
+            "WEAKNESS: The *Brujah* are quick_to anger | always.\nThe rabble said\n===",
+        );
+        assert_eq!(
+            textfixer.get_string(),
+            "- **WEAKNESS:** The \\*Brujah\\* are quick\\_to anger \\| always.\nThe rabble said\n\\==="
+        );
+        textfixer.set_string("CLAN *BRUJAH* (The_Rabble)");
+        assert_eq!(
+            textfixer.get_string(),
+            "### CLAN \\*BRUJAH\\*\n**The\\_Rabble**"
+        );
     }
     #[test]
     fn headers() {
@@ -81,7 +489,15 @@ Followed: By a subheading or something.";
             .lines()
             .map(|s| s.to_owned())
             .collect::<Vec<String>>();
-        let fixed = headers::apply(lines, &textfixer.settings).join("\n");
-        eprintln!("'{fixed}'");
+        let blocks = headers::apply(
+            lines,
+            &textfixer.options.settings,
+            &textfixer.options.heading_levels,
+        );
+        assert!(matches!(blocks[0], blocks::Block::Heading { level: 3, .. }));
+        assert_eq!(
+            blocks[1],
+            blocks::Block::Clarifier("**With a subheading**".to_owned())
+        );
     }
 }
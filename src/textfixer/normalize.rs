@@ -0,0 +1,143 @@
+use super::{defaults, settings};
+use defaults::SettingType as ST;
+use settings::SettingList as SL;
+
+/// Settings of the normalizations, in the order they are reported.
+const NORMALIZATIONS: [ST; 5] = [
+    ST::Ligatures,
+    ST::SoftHyphens,
+    ST::ZeroWidthCharacters,
+    ST::NonBreakingSpaces,
+    ST::LineSeparators,
+];
+
+/// How many characters one enabled normalization changed.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub kind: ST,
+    /// Label of the setting, e.g. "Expand Ligatures".
+    pub label: String,
+    pub count: usize,
+}
+
+/// How many characters each enabled normalization changed during the last fix.
+#[derive(Default, Debug, PartialEq)]
+pub struct Report {
+    pub changes: Vec<Change>,
+}
+
+impl Report {
+    pub fn count(&self, kind: ST) -> usize {
+        self.changes
+            .iter()
+            .find(|c| c.kind == kind)
+            .map_or(0, |c| c.count)
+    }
+
+    /// One line per normalization that changed anything, e.g. "Expand Ligatures: 3 changed".
+    pub fn describe(&self) -> String {
+        let mut description = String::default();
+        for change in self.changes.iter().filter(|c| c.count > 0) {
+            description += &format!("{}: {} changed\n", change.label, change.count);
+        }
+        description
+    }
+}
+
+/// Cleans up the invisible and composed characters PDF copy-paste brings in, which would
+/// otherwise break word lookups and the letter checks of the later stages.
+///
+/// Runs on the whole text before it is split into lines, since line separators are among them.
+pub fn apply(text: &str, settings: &SL<ST>) -> (String, Report) {
+    let enabled = NORMALIZATIONS.map(|kind| settings.check(kind));
+    let mut counts = [0; NORMALIZATIONS.len()];
+    let mut outstring = String::with_capacity(text.len());
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        // Trailing spaces don't count, lines are trimmed later on anyway.
+        let line_end = c == '\u{00AD}'
+            && chars
+                .clone()
+                .find(|next| !matches!(next, ' ' | '\t'))
+                .is_none_or(|next| matches!(next, '\n' | '\r' | '\u{2028}' | '\u{2029}'));
+        let Some((kind, replacement)) = replacement(c, line_end) else {
+            outstring.push(c);
+            continue;
+        };
+        let i = NORMALIZATIONS.iter().position(|k| *k == kind).unwrap();
+        if enabled[i] {
+            counts[i] += 1;
+            outstring += replacement;
+        } else {
+            outstring.push(c);
+        }
+    }
+
+    let changes = NORMALIZATIONS
+        .into_iter()
+        .zip(counts)
+        .zip(enabled)
+        .filter(|(_, enabled)| *enabled)
+        .map(|((kind, count), _)| Change {
+            kind,
+            label: settings.label(kind).to_owned(),
+            count,
+        })
+        .collect();
+    (outstring, Report { changes })
+}
+
+fn replacement(c: char, line_end: bool) -> Option<(ST, &'static str)> {
+    let replacement = match c {
+        'ﬀ' => (ST::Ligatures, "ff"),
+        'ﬁ' => (ST::Ligatures, "fi"),
+        'ﬂ' => (ST::Ligatures, "fl"),
+        'ﬃ' => (ST::Ligatures, "ffi"),
+        'ﬄ' => (ST::Ligatures, "ffl"),
+        'ﬅ' | 'ﬆ' => (ST::Ligatures, "st"),
+        // A soft hyphen ending a line is where the word was broken. It stays for the linebreak
+        // rules, which join the word there without asking the dictionary.
+        '\u{00AD}' if line_end => return None,
+        '\u{00AD}' => (ST::SoftHyphens, ""),
+        '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}' => {
+            (ST::ZeroWidthCharacters, "")
+        }
+        '\u{00A0}' | '\u{2007}' | '\u{202F}' => (ST::NonBreakingSpaces, " "),
+        '\u{2028}' => (ST::LineSeparators, "\n"),
+        '\u{2029}' => (ST::LineSeparators, "\n\n"),
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn normalize() {
+        let mut settings = defaults::setting_list();
+        let (text, report) = apply(
+            "The ﬁrst\u{200B} night\u{00A0}fell.\u{2028}Kin\u{00AD}dred\u{00AD}\nof the ﬂock",
+            &settings,
+        );
+        assert_eq!(text, "The first night fell.\nKindred\u{00AD}\nof the flock");
+        assert_eq!(report.count(ST::Ligatures), 2);
+        assert_eq!(report.count(ST::SoftHyphens), 1);
+        assert_eq!(report.count(ST::ZeroWidthCharacters), 1);
+        assert_eq!(report.count(ST::NonBreakingSpaces), 1);
+        assert_eq!(report.count(ST::LineSeparators), 1);
+        let (text, _) = apply("Kin\u{00AD} \t\ndred", &settings);
+        assert_eq!(text, "Kin\u{00AD} \t\ndred");
+        assert_eq!(
+            report.describe().lines().next(),
+            Some("Expand Ligatures: 2 changed")
+        );
+
+        settings.set(ST::Ligatures, false).unwrap();
+        let (text, report) = apply("ﬁre", &settings);
+        assert_eq!(text, "ﬁre");
+        assert_eq!(report.count(ST::Ligatures), 0);
+        assert!(report.describe().is_empty());
+    }
+}
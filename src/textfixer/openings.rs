@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use super::dictionary::Dictionary;
+use super::texthelpers::WordMatch;
+use super::{defaults, settings};
+use defaults::SettingType as ST;
+use settings::SettingList as SL;
+
+/// Letters in an acronym like "NPC" or "ST", which keeps its capitals.
+const ACRONYM_LETTERS: std::ops::RangeInclusive<usize> = 2..=4;
+
+/// How a word is written in the running text, outside of small caps.
+#[derive(Default)]
+struct Seen {
+    lowercase: bool,
+    capitalized: bool,
+}
+
+/// Rejoins decorative drop caps with the rest of their word, like "T⏎he night", and re-cases
+/// the small capitals chapters open with, like "THE NIGHT WAS cold".
+///
+/// Runs before heading detection, which would take a line of small caps for a heading.
+pub fn apply(lines: Vec<String>, settings: &SL<ST>, dictionary: &Dictionary) -> Vec<String> {
+    let drop_caps = settings.check(ST::DropCaps);
+    let small_caps = settings.check(ST::SmallCaps);
+    if !drop_caps && !small_caps {
+        return lines;
+    }
+    let casing = if small_caps {
+        casing(&lines)
+    } else {
+        HashMap::default()
+    };
+
+    let mut outvec: Vec<String> = Vec::with_capacity(lines.len());
+    let mut line_iter = lines.into_iter().peekable();
+    while let Some(mut line) = line_iter.next() {
+        if !outvec.last().is_none_or(|l| paragraph_start(l)) {
+            outvec.push(line);
+            continue;
+        }
+
+        if drop_caps {
+            if let Some(joined) = line_iter
+                .peek()
+                .and_then(|next| drop_cap(&line, next, false, dictionary))
+            {
+                line = joined;
+                line_iter.next();
+            } else if let Some(joined) = line
+                .split_once(' ')
+                .and_then(|(letter, rest)| drop_cap(letter, rest, true, dictionary))
+            {
+                line = joined;
+            }
+        }
+
+        let caps = caps_words(&line);
+        if small_caps && caps > 0 {
+            let whole_line = caps == line.split_whitespace().count();
+            // A line of capitals running on into lowercase text is no heading.
+            let runs_on = line_iter
+                .peek()
+                .is_some_and(|next| next.starts_with(char::is_lowercase));
+            let lead_in = if whole_line {
+                runs_on
+            } else {
+                let mut words = line.split_whitespace();
+                let first = words.next().unwrap_or_default();
+                let after = words.nth(caps - 1).unwrap_or_default();
+                let ordinary = casing
+                    .get(&first.to_lowercase())
+                    .is_some_and(|seen| seen.lowercase);
+                after.starts_with(char::is_lowercase) && (caps > 1 || ordinary)
+            };
+            if lead_in && line.chars().filter(|c| c.is_alphabetic()).count() > 1 {
+                line = recase(&line, caps, &casing, dictionary);
+                // Recased, the line could pass for a title case heading, so it's joined with
+                // the rest of its sentence right away.
+                if whole_line {
+                    line = format!("{line} {}", line_iter.next().unwrap_or_default());
+                }
+            }
+        }
+        outvec.push(line);
+    }
+    outvec
+}
+
+/// Whether a paragraph, and with it a chapter, can start after this line.
+fn paragraph_start(previous: &str) -> bool {
+    let last_word = previous.split_whitespace().last().unwrap_or_default();
+    previous.is_empty()
+        || WordMatch::EndsSentence.matches(last_word)
+        || !previous.chars().any(char::is_lowercase)
+}
+
+/// Joins a drop cap with the rest of its word, either on the next line or after a space.
+fn drop_cap(letter: &str, rest: &str, same_line: bool, dictionary: &Dictionary) -> Option<String> {
+    let mut chars = letter.chars();
+    let (Some(capital), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    let word = rest.split(|c: char| !c.is_alphabetic()).next()?;
+    let continues = word.starts_with(char::is_lowercase) || !word.chars().any(char::is_lowercase);
+    if !capital.is_uppercase() || word.is_empty() || !continues {
+        return None;
+    }
+
+    let joined = format!("{capital}{word}");
+    // "A" and "I" are words on their own, "A⏎lone figure" is just as likely as "A⏎fter".
+    let standalone = matches!(capital, 'A' | 'I');
+    let join = match (standalone, same_line) {
+        (true, true) => return None,
+        (true, false) => dictionary.knows(&joined) && !dictionary.knows(word),
+        (false, true) => dictionary.knows(&joined),
+        // A lone capital before a word in capitals may just as well be a numeral, like
+        // "CHAPTER⏎V⏎THE FALL".
+        (false, false) => word.starts_with(char::is_lowercase) || dictionary.knows(&joined),
+    };
+    if join {
+        Some(format!("{capital}{rest}"))
+    } else if !same_line {
+        Some(format!("{capital} {rest}"))
+    } else {
+        None
+    }
+}
+
+/// Number of words at the start of the line written in capitals only.
+fn caps_words(line: &str) -> usize {
+    line.split_whitespace()
+        .take_while(|w| w.chars().any(char::is_alphabetic) && !w.chars().any(char::is_lowercase))
+        .count()
+}
+
+/// How the words of the text are written, ignoring words in capitals.
+fn casing(lines: &[String]) -> HashMap<String, Seen> {
+    let mut casing: HashMap<String, Seen> = HashMap::default();
+    for word in lines
+        .iter()
+        .flat_map(|l| l.split(|c: char| !c.is_alphabetic()))
+    {
+        let mut chars = word.chars();
+        let Some(first) = chars.next() else {
+            continue;
+        };
+        if !chars.all(char::is_lowercase) {
+            continue;
+        }
+        let seen = casing.entry(word.to_lowercase()).or_default();
+        seen.lowercase |= first.is_lowercase();
+        seen.capitalized |= first.is_uppercase();
+    }
+    casing
+}
+
+/// Writes the first `count` words in the case they have in the running text, so names stay
+/// capitalized. Short words without vowels that are neither known nor used elsewhere are taken
+/// for acronyms and kept as they are, the other words the text doesn't use elsewhere are
+/// lowercased.
+fn recase(
+    line: &str,
+    count: usize,
+    casing: &HashMap<String, Seen>,
+    dictionary: &Dictionary,
+) -> String {
+    let mut words = line.split(' ').collect::<Vec<_>>();
+    let mut recased = vec![];
+    for (i, word) in words.drain(..count.min(words.len())).enumerate() {
+        let key = word
+            .split(|c: char| !c.is_alphabetic())
+            .find(|k| !k.is_empty())
+            .unwrap_or_default()
+            .to_lowercase();
+        // The word list is far from complete, so words with vowels, like "HAD", are read as words.
+        let acronym = ACRONYM_LETTERS.contains(&key.chars().count())
+            && !key.contains(['a', 'e', 'i', 'o', 'u', 'y'])
+            && !casing.contains_key(&key)
+            && !dictionary.knows(&key);
+        if acronym {
+            recased.push(word.to_owned());
+            continue;
+        }
+        let name = casing
+            .get(&key)
+            .is_some_and(|seen| seen.capitalized && !seen.lowercase);
+        let lowercase = !name && key != "i";
+        let mut word = word.to_lowercase();
+        if i == 0 || !lowercase {
+            word = capitalize(&word);
+        }
+        recased.push(word);
+    }
+    recased.extend(words.into_iter().map(str::to_owned));
+    recased.join(" ")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut capitalized = String::with_capacity(word.len());
+    let mut first = true;
+    for c in word.chars() {
+        if first && c.is_alphabetic() {
+            capitalized.extend(c.to_uppercase());
+            first = false;
+        } else {
+            capitalized.push(c);
+        }
+    }
+    capitalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_owned).collect()
+    }
+    #[test]
+    fn openings() {
+        let settings = defaults::setting_list();
+        let dictionary = Dictionary::default();
+        let fixed = apply(
+            lines("CHAPTER ONE\nT\nhe night was cold.\nA\nnight like no other followed."),
+            &settings,
+            &dictionary,
+        );
+        assert_eq!(
+            fixed,
+            lines("CHAPTER ONE\nThe night was cold.\nA night like no other followed.")
+        );
+
+        let fixed = apply(
+            lines(
+                "CHAPTER TWO\nT\nHE PRINCE AND VITEL WAITED\nfor the night to fall.\nVitel spoke.",
+            ),
+            &settings,
+            &dictionary,
+        );
+        assert_eq!(
+            fixed[1],
+            "The prince and Vitel waited for the night to fall."
+        );
+
+        let fixed = apply(
+            lines("THE NIGHT WAS cold, and the city slept.\nTHE night was long."),
+            &settings,
+            &dictionary,
+        );
+        assert_eq!(fixed[0], "The night was cold, and the city slept.");
+        assert_eq!(fixed[1], "The night was long.");
+
+        let fixed = apply(
+            lines("NPC STATS\nfollow in the appendix below."),
+            &settings,
+            &dictionary,
+        );
+        assert_eq!(fixed, lines("NPC stats follow in the appendix below."));
+
+        let fixed = apply(
+            lines("T he night was cold.\nNPC stats follow.\nTHE KINDRED\nStrength 3"),
+            &settings,
+            &dictionary,
+        );
+        assert_eq!(
+            fixed,
+            lines("The night was cold.\nNPC stats follow.\nTHE KINDRED\nStrength 3")
+        );
+
+        let fixed = apply(lines("CHAPTER\nV\nTHE FALL"), &settings, &dictionary);
+        assert_eq!(fixed, lines("CHAPTER\nV THE FALL"));
+    }
+}
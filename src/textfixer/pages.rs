@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use super::{defaults, settings};
+use defaults::SettingType as ST;
+use settings::SettingList as SL;
+
+/// Fewest lines between two repeats of a line for it to count as a running header.
+const MIN_PAGE_LINES: usize = 10;
+/// Fewest repeats of a line for it to count as a running header. Twice could just as well be a
+/// heading used in two places.
+const MIN_REPEATS: usize = 3;
+
+/// Running headers and footers of a book, like "CHAPTER THREE: THE KINDRED".
+#[derive(Clone, Default)]
+pub struct PageHeaders {
+    /// Known headers, one per line. Matched case-insensitively, ignoring page numbers.
+    pub known: String,
+}
+
+impl PageHeaders {
+    fn is_known(&self, normalized: &str) -> bool {
+        self.known
+            .lines()
+            .map(normalize)
+            .any(|k| !k.is_empty() && k == normalized)
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn egui_render(&mut self, ui: &mut eframe::egui::Ui) -> settings::SettingUpdated {
+        ui.label("Known headers and footers").on_hover_text(
+            "One per line. Removed wherever they show up, page numbers are ignored.",
+        );
+        if ui
+            .add(eframe::egui::TextEdit::multiline(&mut self.known).desired_rows(3))
+            .changed()
+        {
+            settings::SettingUpdated::Updated
+        } else {
+            settings::SettingUpdated::NoChange
+        }
+    }
+}
+
+/// Removes running headers, footers and page numbers left over from page breaks.
+pub fn apply(lines: Vec<String>, settings: &SL<ST>, headers: &PageHeaders) -> Vec<String> {
+    let strip_headers = settings.check(ST::StripPageHeaders);
+    let strip_numbers = settings.check(ST::StripPageNumbers);
+    if !strip_headers && !strip_numbers {
+        return lines;
+    }
+
+    let normalized = lines.iter().map(|l| normalize(l)).collect::<Vec<_>>();
+    let repeating = if strip_headers {
+        repeating(&normalized)
+    } else {
+        vec![false; lines.len()]
+    };
+
+    lines
+        .into_iter()
+        .zip(normalized)
+        .zip(repeating)
+        .filter(|((line, normalized), repeating)| {
+            let page_number = strip_numbers && is_page_number(line);
+            let header = strip_headers && (*repeating || headers.is_known(normalized));
+            !page_number && !header
+        })
+        .map(|((line, _), _)| line)
+        .collect()
+}
+
+/// Lowercased line without page numbers and with collapsed whitespace.
+fn normalize(line: &str) -> String {
+    line.split_whitespace()
+        .filter(|w| !w.chars().all(|c| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn is_page_number(line: &str) -> bool {
+    let line = line.trim();
+    (1..=4).contains(&line.len()) && line.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Marks lines that show up again and again, a page apart.
+fn repeating(normalized: &[String]) -> Vec<bool> {
+    let mut positions = HashMap::<&str, Vec<usize>>::new();
+    for (i, line) in normalized.iter().enumerate() {
+        let looks_like_header = line.chars().filter(|c| c.is_alphabetic()).count() >= 4
+            && !line.ends_with(['.', ',', '!', '?', ':', ';']);
+        if looks_like_header {
+            positions.entry(line).or_default().push(i);
+        }
+    }
+
+    let mut repeating = vec![false; normalized.len()];
+    for indices in positions.values().filter(|p| p.len() >= MIN_REPEATS) {
+        let mut gaps = indices.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+        gaps.sort_unstable();
+        let median = gaps[gaps.len() / 2];
+        // Pages vary a bit in length, so allow some slack around the typical gap.
+        let regular = gaps
+            .iter()
+            .all(|g| g * 4 >= median * 3 && g * 4 <= median * 5);
+        if median >= MIN_PAGE_LINES && regular {
+            indices.iter().for_each(|i| repeating[*i] = true);
+        }
+    }
+    repeating
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn strips_headers_and_page_numbers() {
+        let mut lines = vec![];
+        for page in 1..=3 {
+            lines.push(format!("CHAPTER THREE: THE KINDRED {page}"));
+            lines.extend((0..12).map(|i| format!("Text on page {page}, line {i}.")));
+            lines.push(format!("{page}"));
+        }
+        lines.push("A single Heading".to_owned());
+
+        let settings = defaults::setting_list();
+        let stripped = apply(lines.clone(), &settings, &PageHeaders::default());
+        assert_eq!(stripped.len(), lines.len() - 6);
+        assert!(stripped.iter().all(|l| !l.starts_with("CHAPTER")));
+        assert_eq!(stripped.last().unwrap(), "A single Heading");
+
+        let known = PageHeaders {
+            known: "a single heading".to_owned(),
+        };
+        let stripped = apply(lines, &settings, &known);
+        assert!(stripped.iter().all(|l| l != "A single Heading"));
+
+        let mut lines = vec!["Clan Weakness".to_owned()];
+        lines.extend((0..12).map(|i| format!("Text about Brujah, line {i}.")));
+        lines.push("Clan Weakness".to_owned());
+        lines.push("Text about Gangrel.".to_owned());
+        assert_eq!(apply(lines.clone(), &settings, &known), lines);
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use super::{defaults, settings};
+use defaults::SettingType as ST;
+use settings::SettingList as SL;
+
+/// Profile loaded on start, if it was saved and no other one is asked for.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Named, saved state of the settings, e.g. for "V20 core" or "Werewolf W20".
+/// ```toml
+/// [settings]
+/// MarkdownSectionHeadings = true
+/// MaxHeadingLength = 40
+/// DotStyle = "number"
+/// ```
+#[derive(Default, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub settings: BTreeMap<String, settings::Value>,
+}
+
+impl Profile {
+    pub(crate) fn from_settings(settings: &SL<ST>) -> Self {
+        let settings = settings
+            .list
+            .iter()
+            .filter_map(|s| match toml::Value::try_from(s.kind) {
+                Ok(toml::Value::String(name)) => Some((name, s.value.clone())),
+                _ => None,
+            })
+            .collect();
+        Self { settings }
+    }
+
+    /// Switches the settings to the saved state, and describes the saved settings that were
+    /// skipped, for being unknown to this version or invalid. Settings missing from the profile
+    /// are left alone.
+    pub(crate) fn apply(&self, settings: &mut SL<ST>) -> Vec<String> {
+        let mut skipped = vec![];
+        for (name, value) in &self.settings {
+            let Ok(kind) = toml::Value::String(name.clone()).try_into::<ST>() else {
+                skipped.push(format!("Unknown setting \"{name}\"."));
+                continue;
+            };
+            if let Err(e) = settings.set_value(kind, value.clone()) {
+                skipped.push(format!("{name}: {e}"));
+            }
+        }
+        skipped
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read profile {}.", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid profile {}.", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}.", dir.display()))?;
+        }
+        std::fs::write(path, self.to_toml()?)
+            .with_context(|| format!("Failed to write profile {}.", path.display()))
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn to_toml(&self) -> anyhow::Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+}
+
+/// Directory the profiles are saved in, e.g. `~/.config/darkup/profiles` on Linux.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn dir() -> anyhow::Result<PathBuf> {
+    let config = dirs::config_dir().context("Failed to find the user config directory.")?;
+    Ok(config.join("darkup").join("profiles"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn path(name: &str) -> anyhow::Result<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("\"{name}\" can't be used as a profile name.");
+    }
+    Ok(dir()?.join(format!("{name}.toml")))
+}
+
+/// Names of the saved profiles, sorted.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn list() -> anyhow::Result<Vec<String>> {
+    let dir = dir()?;
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut names = std::fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read {}.", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|e| e == "toml"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn round_trip() {
+        let mut settings = defaults::setting_list();
+        settings.set(ST::SimplifiedHeadings, true).unwrap();
+        settings
+            .set_value(ST::DotStyle, settings::Value::Text("number".to_owned()))
+            .unwrap();
+        let toml = Profile::from_settings(&settings).to_toml().unwrap();
+        assert!(toml.contains("SimplifiedHeadings = true"));
+        assert!(toml.contains("MaxHeadingLength = 40"));
+
+        let mut profile = Profile::parse(&toml).unwrap();
+        profile
+            .settings
+            .insert("FromTheFuture".to_owned(), settings::Value::Bool(true));
+        profile.settings.insert(
+            "BulletCharacter".to_owned(),
+            settings::Value::Text("~".to_owned()),
+        );
+        let mut loaded = defaults::setting_list();
+        let skipped = profile.apply(&mut loaded);
+        assert_eq!(skipped.len(), 2);
+        assert!(loaded.check(ST::SimplifiedHeadings));
+        assert_eq!(loaded.text(ST::DotStyle), "number");
+        assert_eq!(loaded.text(ST::BulletCharacter), "-");
+    }
+}
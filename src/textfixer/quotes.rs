@@ -0,0 +1,179 @@
+use super::blocks::Block;
+use super::dictionary::Dictionary;
+use super::texthelpers::{Match, PunctuationKind};
+use super::{defaults, linebreaks, settings};
+use defaults::SettingType as ST;
+use settings::SettingList as SL;
+
+/// Most lines a quote in quotation marks can span.
+const MAX_QUOTE_LINES: usize = 12;
+/// Most lines of an epigraph without quotation marks, between a heading or blank line and the
+/// attribution.
+const MAX_EPIGRAPH_LINES: usize = 6;
+/// Most words in an attribution, like "— Marcus Vitel, Prince of Washington".
+const MAX_ATTRIBUTION_WORDS: usize = 8;
+/// Longest word in lowercase allowed in a name, like "of" or "von".
+const MAX_PARTICLE_CHARS: usize = 3;
+
+/// Turns quotes and epigraphs followed by an attribution into block quotes, e.g.
+/// ```markdown
+/// > “The blood is the life.”
+/// >
+/// > — Anonymous
+/// ```
+///
+/// Also returns what happened to the linebreaks inside the quotes, with the index of the block
+/// quote as their block.
+pub fn apply(
+    blocks: Vec<Block>,
+    settings: &SL<ST>,
+    ruleset: &[linebreaks::Rule],
+    dictionary: &Dictionary,
+) -> (Vec<Block>, Vec<linebreaks::Join>) {
+    let mut trace = vec![];
+    if !settings.check(ST::Blockquotes) {
+        return (blocks, trace);
+    }
+
+    let mut outvec: Vec<Block> = vec![];
+    for block in blocks {
+        let Some(author) = attribution(&block) else {
+            outvec.push(block);
+            continue;
+        };
+        let Some(start) = quote_start(&outvec) else {
+            outvec.push(block);
+            continue;
+        };
+
+        // The quote is running text like any other, so its lines are joined the usual way.
+        let quote = outvec.split_off(start);
+        let (text, joins) = linebreaks::apply(&quote, ruleset, settings, dictionary);
+        let mut markup = text.lines().map(|l| format!("> {l}")).collect::<Vec<_>>();
+        markup.push(">".to_owned());
+        markup.push(format!("> — {author}"));
+
+        // Block quotes need blank lines around them.
+        if outvec.last().is_some_and(|b| !b.text().is_empty()) {
+            outvec.push(Block::Raw(String::default()));
+        }
+        trace.extend(joins.into_iter().map(|join| linebreaks::Join {
+            block: outvec.len(),
+            ..join
+        }));
+        outvec.push(Block::Raw(markup.join("\n")));
+        outvec.push(Block::Raw(String::default()));
+    }
+    (outvec, trace)
+}
+
+/// Author of an attribution line like "— Anonymous". Only an em dash or its "--" stand-in
+/// counts, a hyphen or en dash is more likely a list item.
+fn attribution(block: &Block) -> Option<&str> {
+    let Block::Paragraph(line) = block else {
+        return None;
+    };
+    let author = ["—", "--"]
+        .iter()
+        .find_map(|dash| line.strip_prefix(dash))?
+        .trim();
+    // A name, like "Marcus Vitel, Prince of Washington", rather than the rest of a sentence.
+    let words = author.split_whitespace().collect::<Vec<_>>();
+    let is_name = words.iter().all(|w| {
+        w.starts_with(char::is_uppercase)
+            || (w.chars().all(char::is_lowercase) && w.chars().count() <= MAX_PARTICLE_CHARS)
+    });
+    // A dash in the middle would make it a parenthetical, like "— and his court —".
+    let is_author = author.starts_with(char::is_uppercase)
+        && words.len() <= MAX_ATTRIBUTION_WORDS
+        && is_name
+        && !author.contains(['—', '–']);
+    is_author.then_some(author)
+}
+
+/// Index of the first line of the quote at the end of `blocks`, if there is one.
+fn quote_start(blocks: &[Block]) -> Option<usize> {
+    let run = blocks
+        .iter()
+        .rev()
+        .take_while(|b| matches!(b, Block::Paragraph(line) if !line.is_empty()))
+        .count();
+    let last = blocks.last()?.text().trim_end();
+
+    let opening = Match::Punctuation(PunctuationKind::OpeningQuote);
+    let closing = Match::Punctuation(PunctuationKind::ClosingQuote);
+    if last.chars().last().is_some_and(|c| closing.matches(c)) {
+        let from = blocks.len() - run.min(MAX_QUOTE_LINES);
+        return (from..blocks.len()).rev().find(|i| {
+            let mut chars = blocks[*i].text().chars();
+            let first = chars.next();
+            first.is_some_and(|c| opening.matches_around(None, c, chars.next()))
+        });
+    }
+    // Epigraphs without quotation marks stand on their own, after a heading or blank line.
+    let ends_sentence = last
+        .chars()
+        .last()
+        .is_some_and(|c| Match::Punctuation(PunctuationKind::EndOfSentence).matches(c));
+    (ends_sentence && (1..=MAX_EPIGRAPH_LINES).contains(&run)).then(|| blocks.len() - run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn quotes() {
+        let settings = defaults::setting_list();
+        let blocks = [
+            "The Prince spoke last.",
+            "“We are the kine’s shepherds,",
+            "not their wolves.”",
+            "— Marcus Vitel",
+            "The court fell silent.",
+        ]
+        .map(|l| Block::Paragraph(l.to_owned()))
+        .to_vec();
+        let (quoted, trace) = apply(
+            blocks,
+            &settings,
+            &defaults::ruleset(),
+            &Dictionary::default(),
+        );
+        assert_eq!(quoted[0].text(), "The Prince spoke last.");
+        assert_eq!(
+            quoted[2].text(),
+            "> “We are the kine’s shepherds, not their wolves.”\n>\n> — Marcus Vitel"
+        );
+        assert_eq!(quoted[4].text(), "The court fell silent.");
+        assert_eq!(trace.len(), 1);
+        assert_eq!((trace[0].block, trace[0].filler.as_str()), (2, " "));
+
+        let parenthetical = ["the Prince", "— and his court —", "met."]
+            .map(|l| Block::Paragraph(l.to_owned()))
+            .to_vec();
+        let (kept, _) = apply(
+            parenthetical.clone(),
+            &settings,
+            &defaults::ruleset(),
+            &Dictionary::default(),
+        );
+        assert_eq!(kept, parenthetical);
+
+        let list = [
+            "Text before.",
+            "",
+            "You have several options.",
+            "- Fight the hunters",
+            "- Flee the city",
+        ]
+        .map(|l| Block::Paragraph(l.to_owned()))
+        .to_vec();
+        let (kept, _) = apply(
+            list.clone(),
+            &settings,
+            &defaults::ruleset(),
+            &Dictionary::default(),
+        );
+        assert_eq!(kept, list);
+    }
+}
@@ -0,0 +1,108 @@
+use super::{defaults, settings};
+use defaults::SettingType as ST;
+use settings::{Choice, SettingList as SL};
+
+const FILLED: [char; 2] = ['•', '●'];
+const HOLLOW: [char; 3] = ['○', '◦', '◯'];
+
+/// How dot ratings like "Strength ••••" are written out.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum DotStyle {
+    /// "Strength ●●●●", the dots as printed.
+    #[default]
+    Dots,
+    /// "Strength 4"
+    Number,
+    /// "Strength ●●●●○", padded with hollow dots to the full scale.
+    Circles,
+}
+
+impl Choice for DotStyle {
+    const ALL: &'static [(Self, &'static str)] = &[
+        (DotStyle::Dots, "dots"),
+        (DotStyle::Number, "number"),
+        (DotStyle::Circles, "circles"),
+    ];
+}
+
+impl DotStyle {
+    fn render(&self, filled: usize, hollow: usize) -> String {
+        match self {
+            Self::Dots => "●".repeat(filled) + &"○".repeat(hollow),
+            Self::Number => filled.to_string(),
+            Self::Circles => {
+                let scale = if hollow > 0 {
+                    filled + hollow
+                } else {
+                    filled.max(5)
+                };
+                "●".repeat(filled) + &"○".repeat(scale - filled)
+            }
+        }
+    }
+}
+
+/// Rewrites dot ratings, and leaves bullet points alone.
+pub fn apply(lines: Vec<String>, settings: &SL<ST>) -> Vec<String> {
+    if !settings.check(ST::DotRatings) {
+        return lines;
+    }
+    let style = settings.choice::<DotStyle>(ST::DotStyle);
+    lines.into_iter().map(|l| ratings(&l, style)).collect()
+}
+
+fn ratings(line: &str, style: DotStyle) -> String {
+    let chars = line.chars().collect::<Vec<_>>();
+    let mut outstring = String::default();
+    let mut i = 0;
+    while i < chars.len() {
+        let is_dot = |c: &char| FILLED.contains(c) || HOLLOW.contains(c);
+        if !is_dot(&chars[i]) {
+            outstring.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let run = chars[i..]
+            .iter()
+            .take_while(|c| is_dot(c))
+            .collect::<Vec<_>>();
+        let filled = run.iter().take_while(|c| FILLED.contains(c)).count();
+        let hollow = run.len() - filled;
+        let next = chars.get(i + run.len());
+
+        // A lone dot is a bullet point, unless it trails a name, like "Strength •".
+        let trails_text = outstring.chars().any(|c| !c.is_whitespace())
+            && next.is_none_or(|c| matches!(c, ',' | ';' | ')' | '.'));
+        let is_rating = run.len() > 1 || trails_text;
+
+        if is_rating {
+            outstring += &style.render(filled, hollow);
+        } else {
+            outstring.extend(run.iter().copied());
+        }
+        i += run.len();
+    }
+    outstring
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn ratings_and_bullets() {
+        assert_eq!(ratings("Strength ••••", DotStyle::Number), "Strength 4");
+        assert_eq!(ratings("Strength •", DotStyle::Number), "Strength 1");
+        assert_eq!(ratings("Resources •••○○", DotStyle::Number), "Resources 3");
+        assert_eq!(
+            ratings("Resources •••", DotStyle::Circles),
+            "Resources ●●●○○"
+        );
+        assert_eq!(ratings("•• Dread Gaze", DotStyle::Dots), "●● Dread Gaze");
+        assert_eq!(
+            ratings("• A bullet point", DotStyle::Number),
+            "• A bullet point"
+        );
+        assert_eq!(ratings("•Bulletpoint", DotStyle::Number), "•Bulletpoint");
+    }
+}
@@ -0,0 +1,99 @@
+use std::path::Path;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use super::linebreaks;
+
+/// How the rules of a rule file are combined with the current ones.
+#[derive(Default, Serialize, Deserialize)]
+pub enum Mode {
+    /// Rules from the file are tried first, falling back on the current ones.
+    #[default]
+    Extend,
+    /// Only the rules from the file are used.
+    Replace,
+}
+
+/// User editable set of linebreak rules, e.g.
+/// ```toml
+/// mode = "Extend"
+///
+/// [[rule]]
+/// # Text with conti-⏎nuation
+/// setting = ["SmartHyphenRemoval", true] # optional
+/// previous = { symbol = { Punctuation = "Hyphen" }, on_match = "Remove" }
+/// following = { symbol = { Letter = "Lowercase" } }
+/// filler = "None"
+/// ```
+#[derive(Default, Serialize, Deserialize)]
+pub struct RuleFile {
+    #[serde(default)]
+    pub mode: Mode,
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<linebreaks::Rule>,
+}
+
+impl RuleFile {
+    /// The default rules, replacing whatever rules there are.
+    pub fn defaults() -> Self {
+        Self {
+            mode: Mode::Replace,
+            rules: super::defaults::ruleset(),
+        }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rule file {}.", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid rule file {}.", path.display()))
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn to_toml(&self) -> anyhow::Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Combines the rules of this file with an existing ruleset.
+    pub(crate) fn apply(self, ruleset: Vec<linebreaks::Rule>) -> Vec<linebreaks::Rule> {
+        match self.mode {
+            Mode::Extend => self.rules.into_iter().chain(ruleset).collect(),
+            Mode::Replace => self.rules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::defaults;
+    use super::*;
+    #[test]
+    fn default_rules_round_trip() {
+        let file = RuleFile {
+            mode: Mode::Replace,
+            rules: defaults::ruleset(),
+        };
+        let toml = file.to_toml().unwrap();
+        let parsed = RuleFile::parse(&toml).unwrap();
+        assert_eq!(parsed.rules.len(), defaults::ruleset().len());
+        assert_eq!(parsed.to_toml().unwrap(), toml);
+    }
+    #[test]
+    fn extend_puts_user_rules_first() {
+        let file = RuleFile::parse(
+            r#"
+            [[rule]]
+            previous = { symbol = { Punctuation = "Hyphen" } }
+            following = { symbol = { Letter = "Lowercase" } }
+            filler = "None"
+            "#,
+        )
+        .unwrap();
+        let ruleset = file.apply(defaults::ruleset());
+        assert_eq!(ruleset.len(), defaults::ruleset().len() + 1);
+        assert!(ruleset[0].previous.on_match == super::super::texthelpers::Action::Leave);
+    }
+}
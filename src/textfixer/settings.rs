@@ -1,11 +1,56 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
 #[derive(PartialEq)]
 pub enum SettingUpdated {
     NoChange,
     Updated,
 }
+
+/// Current value of a setting, as saved in profiles.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Bool(bool),
+    Number(usize),
+    /// The picked option of a choice, or free text.
+    Text(String),
+}
+
+/// How a setting is shown, and which values it takes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Widget {
+    Checkbox,
+    Choice(Vec<&'static str>),
+    Slider(RangeInclusive<usize>),
+    TextField,
+}
+
+/// Named options of a choice setting, by the names used in profiles and on the command line.
+pub trait Choice: Copy + PartialEq + Default + 'static {
+    const ALL: &'static [(Self, &'static str)];
+
+    fn name(&self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(option, _)| option == self)
+            .map(|(_, name)| *name)
+            .unwrap_or_default()
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(option, _)| *option)
+    }
+}
+
 pub struct Setting<SettingType> {
     pub kind: SettingType,
-    pub enabled: bool,
+    pub value: Value,
+    pub widget: Widget,
     pub label: String,
     pub explanation: String,
 }
@@ -19,35 +64,170 @@ where
             kind,
             label: label.to_owned(),
             explanation: explanation.to_owned(),
-            enabled: default_enabled,
+            value: Value::Bool(default_enabled),
+            widget: Widget::Checkbox,
+        }
+    }
+
+    /// Setting picking one of a few named options, starting out with the default option.
+    pub fn choice<C: Choice>(kind: SettingType, label: &str, explanation: &str) -> Self {
+        Self {
+            value: Value::Text(C::default().name().to_owned()),
+            widget: Widget::Choice(C::ALL.iter().map(|(_, name)| *name).collect()),
+            ..Self::new(kind, label, explanation, false)
+        }
+    }
+
+    /// Setting holding a whole number within a range.
+    pub fn number(
+        kind: SettingType,
+        label: &str,
+        explanation: &str,
+        range: RangeInclusive<usize>,
+        default: usize,
+    ) -> Self {
+        Self {
+            value: Value::Number(default),
+            widget: Widget::Slider(range),
+            ..Self::new(kind, label, explanation, false)
+        }
+    }
+
+    /// Setting holding free text.
+    pub fn text(kind: SettingType, label: &str, explanation: &str, default: &str) -> Self {
+        Self {
+            value: Value::Text(default.to_owned()),
+            widget: Widget::TextField,
+            ..Self::new(kind, label, explanation, false)
+        }
+    }
+
+    /// Command line flag name derived from the label, e.g. "Smart Hyphen Removal" -> "smart-hyphen-removal".
+    pub fn flag(&self) -> String {
+        self.label.to_lowercase().replace(' ', "-")
+    }
+
+    /// Whether the widget of this setting accepts the value.
+    pub fn accepts(&self, value: &Value) -> bool {
+        match (&self.widget, value) {
+            (Widget::Checkbox, Value::Bool(_)) | (Widget::TextField, Value::Text(_)) => true,
+            (Widget::Choice(options), Value::Text(text)) => options.contains(&text.as_str()),
+            (Widget::Slider(range), Value::Number(number)) => range.contains(number),
+            _ => false,
         }
     }
+
+    #[cfg(feature = "gui")]
+    fn egui_render(&mut self, ui: &mut eframe::egui::Ui) -> bool {
+        use eframe::egui;
+        let response = match (&self.widget, &mut self.value) {
+            (Widget::Checkbox, Value::Bool(enabled)) => ui.checkbox(enabled, self.label.clone()),
+            (Widget::Choice(options), Value::Text(selected)) => {
+                let mut changed = false;
+                let mut response = egui::ComboBox::from_label(self.label.clone())
+                    .selected_text(selected.clone())
+                    .show_ui(ui, |ui| {
+                        for option in options {
+                            changed |= ui
+                                .selectable_value(selected, option.to_string(), *option)
+                                .changed();
+                        }
+                    })
+                    .response;
+                if changed {
+                    response.mark_changed();
+                }
+                response
+            }
+            (Widget::Slider(range), Value::Number(number)) => {
+                ui.add(egui::Slider::new(number, range.clone()).text(self.label.clone()))
+            }
+            (Widget::TextField, Value::Text(text)) => {
+                ui.horizontal(|ui| {
+                    ui.label(self.label.clone());
+                    ui.text_edit_singleline(text)
+                })
+                .inner
+            }
+            _ => ui.label(format!("{}: invalid value {:?}", self.label, self.value)),
+        };
+        response.on_hover_text(self.explanation.clone()).changed()
+    }
 }
 
 pub struct SettingList<SettingType> {
-    pub list: Vec<Setting<SettingType>>,
+    pub(crate) list: Vec<Setting<SettingType>>,
 }
 
 impl<SettingType> SettingList<SettingType>
 where
     SettingType: PartialEq,
 {
-    pub fn check(&self, kind: SettingType) -> bool {
+    fn value(&self, kind: SettingType) -> Option<&Value> {
+        self.list.iter().find(|s| s.kind == kind).map(|s| &s.value)
+    }
+
+    /// All settings, in the order the application shows them.
+    pub fn iter(&self) -> impl Iterator<Item = &Setting<SettingType>> {
+        self.list.iter()
+    }
+
+    /// Label of a setting, as shown in the application.
+    pub fn label(&self, kind: SettingType) -> &str {
         self.list
             .iter()
             .find(|s| s.kind == kind)
-            .map(|s| s.enabled)
-            .expect("Setting type without matching setting.")
+            .map_or("unknown setting", |s| s.label.as_str())
+    }
+
+    /// Whether a setting is enabled. Settings missing from the list count as disabled.
+    pub fn check(&self, kind: SettingType) -> bool {
+        self.value(kind) == Some(&Value::Bool(true))
     }
 
+    /// Number held by a setting, or 0 if it's missing.
+    pub fn number(&self, kind: SettingType) -> usize {
+        match self.value(kind) {
+            Some(Value::Number(number)) => *number,
+            _ => 0,
+        }
+    }
+
+    /// Picked option or text of a setting, or "" if it's missing.
+    pub fn text(&self, kind: SettingType) -> &str {
+        match self.value(kind) {
+            Some(Value::Text(text)) => text,
+            _ => "",
+        }
+    }
+
+    /// Picked option of a choice setting, or the default option if it's missing.
+    pub fn choice<C: Choice>(&self, kind: SettingType) -> C {
+        C::from_name(self.text(kind)).unwrap_or_default()
+    }
+
+    /// Enables or disables a checkbox setting.
+    pub fn set(&mut self, kind: SettingType, enabled: bool) -> anyhow::Result<()> {
+        self.set_value(kind, Value::Bool(enabled))
+    }
+
+    /// Changes the value of any kind of setting, if it fits the setting.
+    pub fn set_value(&mut self, kind: SettingType, value: Value) -> anyhow::Result<()> {
+        let Some(setting) = self.list.iter_mut().find(|s| s.kind == kind) else {
+            anyhow::bail!("Setting isn't in the list.");
+        };
+        if !setting.accepts(&value) {
+            anyhow::bail!("{:?} isn't a valid value for \"{}\".", value, setting.label);
+        }
+        setting.value = value;
+        Ok(())
+    }
+
+    #[cfg(feature = "gui")]
     pub fn egui_render(&mut self, ui: &mut eframe::egui::Ui) -> SettingUpdated {
         let mut updated = SettingUpdated::NoChange;
         self.list.iter_mut().for_each(|setting| {
-            let cb = ui
-                .checkbox(&mut setting.enabled, setting.label.clone())
-                .on_hover_text(setting.explanation.clone());
-
-            if cb.clicked() {
+            if setting.egui_render(ui) {
                 updated = SettingUpdated::Updated;
             }
         });
@@ -55,3 +235,21 @@ where
         updated
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::textfixer::{defaults, headers::HeadingStyle};
+    use defaults::SettingType as ST;
+    #[test]
+    fn typed_values() {
+        let mut settings = defaults::setting_list();
+        assert!(settings.set(ST::HeadingStyle, true).is_err());
+        assert_eq!(settings.text(ST::HeadingStyle), "atx");
+        settings
+            .set_value(ST::HeadingStyle, Value::Text("setext".to_owned()))
+            .unwrap();
+        assert!(settings.choice::<HeadingStyle>(ST::HeadingStyle) == HeadingStyle::Setext);
+        assert!(settings.set(ST::SmartHyphenRemoval, false).is_ok());
+    }
+}
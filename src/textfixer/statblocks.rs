@@ -0,0 +1,212 @@
+use super::blocks::Block;
+use super::{defaults, settings};
+use defaults::SettingType as ST;
+use settings::{Choice, SettingList as SL};
+
+/// Fewest rated lines in a row that are taken for a flattened column of traits.
+const MIN_COLUMN_LINES: usize = 3;
+/// Most words in a trait name, like "Animal Ken".
+const MAX_TRAIT_WORDS: usize = 3;
+/// Highest rating written as a number, as the dots of the eldest go up to ten.
+const MAX_RATING: usize = 10;
+
+/// How stat blocks are written out.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum StatBlockStyle {
+    #[default]
+    Table,
+    /// A definition list, "Term" followed by ": definition".
+    Definitions,
+}
+
+impl Choice for StatBlockStyle {
+    const ALL: &'static [(Self, &'static str)] = &[
+        (StatBlockStyle::Table, "table"),
+        (StatBlockStyle::Definitions, "definitions"),
+    ];
+}
+
+/// One line of a stat block, a labelled list like "Attributes: Strength 2, Dexterity 3" or a
+/// single rated trait like "Strength ●●○○○".
+enum Stat {
+    List { label: String, traits: Vec<String> },
+    Trait { name: String, rating: String },
+}
+
+/// Turns runs of trait lists and rated traits into Markdown tables or definition lists.
+pub fn apply(blocks: Vec<Block>, settings: &SL<ST>) -> Vec<Block> {
+    if !settings.check(ST::StatBlocks) {
+        return blocks;
+    }
+    let definitions =
+        settings.choice::<StatBlockStyle>(ST::StatBlockStyle) == StatBlockStyle::Definitions;
+
+    let mut outvec = vec![];
+    let mut run = vec![];
+    let mut block_iter = blocks.into_iter().peekable();
+    while let Some(block) = block_iter.next() {
+        let Some(mut stat) = stat(&block) else {
+            flush(&mut outvec, &mut run, definitions);
+            // Tables and definition lists need a blank line after them, too.
+            let after_markup =
+                matches!(outvec.last(), Some(Block::Raw(markup)) if !markup.is_empty());
+            if after_markup && !block.text().is_empty() {
+                outvec.push(Block::Raw(String::default()));
+            }
+            outvec.push(block);
+            continue;
+        };
+        // Long lists wrap, and end the line with a comma.
+        let mut source = vec![block];
+        while let Stat::List { traits, .. } = &mut stat {
+            let wrapped = source.last().is_some_and(|b| b.text().ends_with(','));
+            let Some(continued) = block_iter.peek().filter(|_| wrapped).and_then(continuation)
+            else {
+                break;
+            };
+            traits.extend(continued);
+            source.extend(block_iter.next());
+        }
+        run.push((source, stat));
+    }
+    flush(&mut outvec, &mut run, definitions);
+    outvec
+}
+
+fn stat(block: &Block) -> Option<Stat> {
+    let Block::Paragraph(line) = block else {
+        return None;
+    };
+    if let Some((label, list)) = line.split_once(':') {
+        let traits = traits(list)?;
+        let label_ok = label.split_whitespace().count() <= MAX_TRAIT_WORDS
+            && label.starts_with(|c: char| c.is_uppercase());
+        return (label_ok && traits.len() > 1).then(|| Stat::List {
+            label: label.trim().to_owned(),
+            traits,
+        });
+    }
+    // On its own a number is as likely a reference, like "Chapter 3", so only dots count.
+    let (name, rating) = rated_trait(line)
+        .filter(|(_, rating)| !rating.starts_with(|c: char| c.is_ascii_digit()))?;
+    Some(Stat::Trait {
+        name: name.to_owned(),
+        rating: rating.to_owned(),
+    })
+}
+
+/// Rated traits continuing a wrapped list on the next line.
+fn continuation(block: &Block) -> Option<Vec<String>> {
+    match block {
+        Block::Paragraph(line) if !line.contains(':') => traits(line),
+        _ => None,
+    }
+}
+
+/// Comma separated traits that all have a rating, like "Strength 2, Dexterity 3".
+fn traits(list: &str) -> Option<Vec<String>> {
+    list.split([',', ';'])
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| rated_trait(t).map(|_| t.to_owned()))
+        .collect()
+}
+
+/// Splits "Animal Ken ●●○" into its name and rating.
+fn rated_trait(text: &str) -> Option<(&str, &str)> {
+    let (name, rating) = text.trim().trim_end_matches([',', '.']).rsplit_once(' ')?;
+    let name = name.trim();
+    let is_rating = (!rating.is_empty() && rating.chars().all(|c| "●○•◦◯".contains(c)))
+        || rating.parse().is_ok_and(|r: usize| r <= MAX_RATING);
+    let is_name = name.starts_with(|c: char| c.is_uppercase())
+        && name.split_whitespace().count() <= MAX_TRAIT_WORDS
+        && !name.ends_with(',');
+    (is_rating && is_name).then_some((name, rating))
+}
+
+/// Writes out a run of stat lines, as a table or definition list if it is one.
+fn flush(outvec: &mut Vec<Block>, run: &mut Vec<(Vec<Block>, Stat)>, definitions: bool) {
+    if run.is_empty() {
+        return;
+    }
+    let lists = run.iter().any(|(_, s)| matches!(s, Stat::List { .. }));
+    if !lists && run.len() < MIN_COLUMN_LINES {
+        // Too few rated lines to be sure, leave them to the other stages.
+        outvec.extend(run.drain(..).flat_map(|(source, _)| source));
+        return;
+    }
+
+    let rows = run.drain(..).map(|(_, s)| match s {
+        Stat::List { label, traits } => (label, traits.join(", ")),
+        Stat::Trait { name, rating } => (name, rating),
+    });
+    let markup = if definitions {
+        rows.map(|(term, definition)| format!("{term}\n: {definition}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        // A single rated trait in a mixed run reads fine as a category with one trait.
+        let header = if lists {
+            "| Category | Traits |"
+        } else {
+            "| Trait | Rating |"
+        };
+        let mut table = format!("{header}\n| --- | --- |");
+        for (name, value) in rows {
+            table += &format!("\n| {name} | {value} |");
+        }
+        table
+    };
+
+    // Tables and definition lists need blank lines around them.
+    if outvec.last().is_some_and(|b| !b.text().is_empty()) {
+        outvec.push(Block::Raw(String::default()));
+    }
+    outvec.push(Block::Raw(markup));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn paragraphs(text: &str) -> Vec<Block> {
+        text.lines()
+            .map(|l| Block::Paragraph(l.to_owned()))
+            .collect()
+    }
+    #[test]
+    fn stat_blocks() {
+        let settings = defaults::setting_list();
+        let blocks = apply(
+            paragraphs(
+                "Attributes: Strength 2, Dexterity 3,\nStamina 2\nAbilities: Brawl 3, Dodge 2\nShe hunts alone.",
+            ),
+            &settings,
+        );
+        assert_eq!(
+            blocks[0].text(),
+            "| Category | Traits |\n| --- | --- |\n| Attributes | Strength 2, Dexterity 3, Stamina 2 |\n| Abilities | Brawl 3, Dodge 2 |"
+        );
+        assert_eq!(blocks[2].text(), "She hunts alone.");
+
+        let blocks = apply(
+            paragraphs("Strength ●●○○○\nDexterity ●●●○○\nStamina ●○○○○"),
+            &settings,
+        );
+        assert!(blocks[0].text().starts_with("| Trait | Rating |"));
+        assert_eq!(blocks.len(), 1);
+
+        let blocks = apply(
+            paragraphs("Disciplines: Celerity 2, Potence 1\nGeneration ●●●○○"),
+            &settings,
+        );
+        assert!(blocks[0].text().starts_with("| Category | Traits |"));
+
+        let blocks = apply(paragraphs("Chapter 1\nChapter 2\nChapter 3"), &settings);
+        assert_eq!(blocks, paragraphs("Chapter 1\nChapter 2\nChapter 3"));
+
+        let blocks = apply(paragraphs("Strength ●●○○○\nThe end."), &settings);
+        assert_eq!(blocks[0], Block::Paragraph("Strength ●●○○○".to_owned()));
+        let blocks = apply(paragraphs("Note: see page 12"), &settings);
+        assert_eq!(blocks[0], Block::Paragraph("Note: see page 12".to_owned()));
+    }
+}
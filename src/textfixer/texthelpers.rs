@@ -1,19 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use super::dictionary::Verdict;
+use super::settings::Choice;
+
+#[derive(Serialize, Deserialize)]
 pub enum Case {
     Lowercase,
     Uppercase,
     Anycase,
 }
 
+#[derive(Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum PunctuationKind {
     AnyPunctuation,
     EndOfSentence,
     Continuation,
     Parantheses,
+    /// Any hyphen, including non-breaking and soft hyphens.
     Hyphen,
+    /// Em dash, or horizontal bar.
     Dash,
+    EnDash,
     Slash,
+    OpeningQuote,
+    ClosingQuote,
+    Ellipsis,
+}
+
+const HYPHENS: [char; 4] = ['-', '\u{2010}', '\u{2011}', '\u{00AD}'];
+const OPENING_QUOTES: [char; 5] = ['“', '‘', '„', '«', '‹'];
+const CLOSING_QUOTES: [char; 4] = ['”', '’', '»', '›'];
+
+/// Typographic punctuation beyond ASCII, as found in text copied from PDFs.
+fn is_unicode_punctuation(candidate: char) -> bool {
+    matches!(
+        candidate,
+        '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205E}' | '«' | '»' | '¡' | '¿' | '§' | '¶' | '·' | '\u{00AD}'
+    )
+}
+
+/// Whether a straight quote opens a quote, which it does where a word starts.
+fn opens_straight_quote(before: Option<char>, after: Option<char>) -> bool {
+    let word_start = before.is_none_or(|c| {
+        c.is_whitespace() || OPENING_QUOTES.contains(&c) || matches!(c, '(' | '[' | '—' | '–')
+    });
+    word_start && after.is_some_and(|c| !c.is_whitespace())
 }
 
+#[derive(Serialize, Deserialize)]
 pub enum Match {
     Anymatch,
     Exact(char),
@@ -25,6 +60,16 @@ pub enum Match {
 
 impl Match {
     pub fn matches(&self, candidate: char) -> bool {
+        self.matches_around(None, candidate, None)
+    }
+    /// Like `matches`, with the characters around the candidate, which tell an apostrophe like
+    /// "Kindred’s" from a closing quote, and whether a straight quote opens or closes.
+    pub fn matches_around(
+        &self,
+        before: Option<char>,
+        candidate: char,
+        after: Option<char>,
+    ) -> bool {
         use Case as C;
         use Match as M;
         use PunctuationKind as P;
@@ -39,50 +84,180 @@ impl Match {
             M::Whitespace => candidate.is_whitespace(),
             M::Linebreak => candidate == '\n',
             M::Punctuation(punctuation) => match punctuation {
-                P::AnyPunctuation => candidate.is_ascii_punctuation(),
-                P::EndOfSentence => matches!(candidate, '.' | '!' | '?'),
+                P::AnyPunctuation => {
+                    candidate.is_ascii_punctuation() || is_unicode_punctuation(candidate)
+                }
+                P::EndOfSentence => matches!(candidate, '.' | '!' | '?' | '…'),
                 P::Continuation => matches!(candidate, ',' | ':' | ';'),
                 P::Parantheses => matches!(candidate, '(' | ')' | '[' | ']'),
-                P::Hyphen => candidate == '-',
-                P::Dash => candidate == '—',
+                P::Hyphen => HYPHENS.contains(&candidate),
+                P::Dash => matches!(candidate, '—' | '―'),
+                P::EnDash => candidate == '–',
                 P::Slash => candidate == '/',
+                P::OpeningQuote => {
+                    OPENING_QUOTES.contains(&candidate)
+                        || (candidate == '"' && opens_straight_quote(before, after))
+                }
+                P::ClosingQuote => match candidate {
+                    '’' => !after.is_some_and(char::is_alphabetic),
+                    '"' => !opens_straight_quote(before, after),
+                    _ => CLOSING_QUOTES.contains(&candidate),
+                },
+                P::Ellipsis => candidate == '…',
             },
         }
     }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Default, Serialize, Deserialize)]
 pub enum Action {
     Remove,
+    #[default]
     Leave,
 }
 
+/// Checks the whole word touching the linebreak, i.e. the last word before it or the first word after it.
+#[derive(Serialize, Deserialize)]
+pub enum WordMatch {
+    OneOf(Vec<String>),
+    Prefix(String),
+    Suffix(String),
+    Capitalized,
+    /// The word ends a sentence, even inside closing quotes, like `night.”`.
+    EndsSentence,
+    /// The dictionary considers the word hyphenated across the linebreak a compound, like
+    /// "blood-⏎soaked". Never matches away from a hyphen.
+    Compound,
+}
+
+impl WordMatch {
+    pub fn matches(&self, candidate: &str) -> bool {
+        self.matches_break(candidate, None)
+    }
+    /// Like `matches`, with the dictionary's verdict on a hyphen at the linebreak, if any.
+    pub fn matches_break(&self, candidate: &str, hyphen: Option<Verdict>) -> bool {
+        use WordMatch as W;
+        match self {
+            W::OneOf(words) => words.iter().any(|w| w == candidate),
+            W::Prefix(prefix) => candidate.starts_with(prefix.as_str()),
+            W::Suffix(suffix) => candidate.ends_with(suffix.as_str()),
+            W::Capitalized => candidate.chars().next().is_some_and(char::is_uppercase),
+            W::EndsSentence => candidate
+                .trim_end_matches(|c| CLOSING_QUOTES.contains(&c) || c == '"')
+                .chars()
+                .last()
+                .is_some_and(|c| Match::Punctuation(PunctuationKind::EndOfSentence).matches(c)),
+            W::Compound => hyphen == Some(Verdict::Compound),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct SymbolPredicate {
     pub symbol: Match,
+    #[serde(default)]
     pub on_match: Action,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word: Option<WordMatch>,
 }
 
 impl SymbolPredicate {
     pub fn new(symbol: Match, on_match: Action) -> Self {
-        Self { symbol, on_match }
+        Self {
+            symbol,
+            on_match,
+            word: None,
+        }
+    }
+    pub fn with_word(mut self, word: WordMatch) -> Self {
+        self.word = Some(word);
+        self
+    }
+    /// Whether the character and word at one side of a linebreak match. `around` are the
+    /// characters before and after the symbol, and `hyphen` is the dictionary's verdict on a
+    /// hyphen at the linebreak, if any.
+    pub fn matches(
+        &self,
+        symbol: char,
+        around: (Option<char>, Option<char>),
+        word: &str,
+        hyphen: Option<Verdict>,
+    ) -> bool {
+        self.symbol.matches_around(around.0, symbol, around.1)
+            && self
+                .word
+                .as_ref()
+                .is_none_or(|w| w.matches_break(word, hyphen))
     }
 }
 
+/// Character starting the list items made from bullet points.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum Bullet {
+    #[default]
+    Dash,
+    Asterisk,
+    Plus,
+}
+
+impl Choice for Bullet {
+    const ALL: &'static [(Self, &'static str)] = &[
+        (Bullet::Dash, "-"),
+        (Bullet::Asterisk, "*"),
+        (Bullet::Plus, "+"),
+    ];
+}
+
+#[derive(Serialize, Deserialize)]
 pub enum Filler {
     None,
     Space,
     Linebreak,
     Exact(String),
+    /// A new list item, starting with the bullet character from the settings.
+    Bullet,
 }
 
 impl Filler {
-    pub fn get(&self) -> &str {
+    /// Text replacing the linebreak, with `list_item` being the start of a new list item.
+    pub fn get<'a>(&'a self, list_item: &'a str) -> &'a str {
         use Filler as F;
         match self {
             F::None => "",
             F::Space => " ",
             F::Linebreak => "\n",
             F::Exact(filler) => filler,
+            F::Bullet => list_item,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn unicode_punctuation() {
+        use PunctuationKind as P;
+        let hyphen = Match::Punctuation(P::Hyphen);
+        assert!(['-', '‐', '‑', '\u{00AD}']
+            .iter()
+            .all(|c| hyphen.matches(*c)));
+        assert!(!hyphen.matches('–'));
+        assert!(Match::Punctuation(P::EnDash).matches('–'));
+        assert!(Match::Punctuation(P::EndOfSentence).matches('…'));
+        assert!(Match::Punctuation(P::AnyPunctuation).matches('”'));
+        assert!(WordMatch::EndsSentence.matches("night.”"));
+        assert!(WordMatch::EndsSentence.matches("you?’”"));
+        assert!(!WordMatch::EndsSentence.matches("shepherds”"));
+        assert!(WordMatch::EndsSentence.matches("\"Enough.\""));
+
+        let opening = Match::Punctuation(P::OpeningQuote);
+        let closing = Match::Punctuation(P::ClosingQuote);
+        assert!(!closing.matches_around(Some('d'), '’', Some('s')));
+        assert!(closing.matches_around(Some('.'), '’', None));
+        assert!(opening.matches_around(Some(' '), '"', Some('W')));
+        assert!(!closing.matches_around(Some(' '), '"', Some('W')));
+        assert!(closing.matches_around(Some('.'), '"', Some(' ')));
+        assert!(!opening.matches_around(Some('.'), '"', Some(' ')));
+    }
+}
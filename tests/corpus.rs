@@ -0,0 +1,120 @@
+//! Golden file tests, running the whole pipeline over the cases in `tests/corpus/`.
+//!
+//! Each case is a directory holding an `input.txt` and the `expected.md` it should turn into.
+//! An optional `settings.toml` switches settings by their command line flag names, e.g.
+//! `section-headings = false` or `dot-style = "number"`.
+//!
+//! Run with `DARKUP_BLESS=1` to write the current output to `expected.md` instead of comparing.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use darkup::{Textfixer, Value};
+
+fn corpus_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus")
+}
+
+fn run_case(dir: &Path) -> anyhow::Result<String> {
+    let mut textfixer = Textfixer::default();
+    let settings_path = dir.join("settings.toml");
+    if settings_path.exists() {
+        let settings: HashMap<String, Value> =
+            toml::from_str(&std::fs::read_to_string(settings_path)?)?;
+        for (flag, value) in settings {
+            let kind = textfixer
+                .settings()
+                .iter()
+                .find(|s| s.flag() == flag)
+                .map(|s| s.kind)
+                .ok_or_else(|| anyhow::anyhow!("Unknown setting \"{flag}\""))?;
+            textfixer.set_setting_value(kind, value)?;
+        }
+    }
+    textfixer.set_string(&std::fs::read_to_string(dir.join("input.txt"))?);
+    Ok(textfixer.get_string().to_owned())
+}
+
+#[test]
+fn corpus() {
+    let bless = std::env::var_os("DARKUP_BLESS").is_some();
+    let mut cases = std::fs::read_dir(corpus_dir())
+        .expect("Missing tests/corpus directory.")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.join("input.txt").exists())
+        .collect::<Vec<_>>();
+    cases.sort();
+    assert!(!cases.is_empty(), "No cases in tests/corpus.");
+
+    let mut failures = vec![];
+    for dir in &cases {
+        let name = dir.file_name().unwrap().to_string_lossy();
+        let output = match run_case(dir) {
+            Ok(output) => output + "\n",
+            Err(e) => {
+                failures.push(format!("{name}: {e}"));
+                continue;
+            }
+        };
+
+        let expected_path = dir.join("expected.md");
+        if bless {
+            std::fs::write(&expected_path, &output).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&expected_path).unwrap_or_default();
+        if expected != output {
+            let diff = similar::TextDiff::from_lines(&expected, &output)
+                .unified_diff()
+                .header("expected.md", "output")
+                .to_string();
+            failures.push(format!("{name}:\n{diff}"));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} corpus cases failed, rerun with DARKUP_BLESS=1 to accept the output.\n\n{}",
+        failures.len(),
+        cases.len(),
+        failures.join("\n")
+    );
+}
+
+/// Times the pipeline on a book-sized text, stitched together from the corpus inputs.
+///
+/// Run with `cargo test --release book_sized -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn book_sized() {
+    let mut cases = std::fs::read_dir(corpus_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path().join("input.txt"))
+        .filter(|path| path.exists())
+        .collect::<Vec<_>>();
+    cases.sort();
+    let chapter = cases
+        .iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+    // Roughly a 300 page core book, at 50 lines a page.
+    let repeats = 15_000 / chapter.lines().count() + 1;
+    let book = chapter.repeat(repeats);
+
+    // Worst case for merging, a single paragraph running on for the whole book.
+    let paragraph = "and the night goes on, the hunt goes on\n".repeat(15_000);
+
+    for (name, text) in [("book", book), ("paragraph", paragraph)] {
+        let mut textfixer = Textfixer::default();
+        let start = std::time::Instant::now();
+        textfixer.set_string(&text);
+        let elapsed = start.elapsed();
+        eprintln!(
+            "Fixed {name} of {} lines ({} KiB) in {elapsed:?}",
+            text.lines().count(),
+            text.len() / 1024
+        );
+        assert!(!textfixer.get_string().is_empty());
+    }
+}